@@ -0,0 +1,87 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::solutions::Solution;
+
+pub struct Timing {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+pub struct DayTimings {
+    pub day: u8,
+    pub parse: Timing,
+    pub part1: Timing,
+    pub part2: Timing,
+}
+
+fn time<T, F: FnMut() -> T>(runs: usize, mut f: F) -> Timing {
+    let mut samples = Vec::with_capacity(runs);
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        black_box(f());
+        samples.push(start.elapsed());
+    }
+    samples.sort();
+    Timing {
+        min: samples[0],
+        median: samples[samples.len() / 2],
+        max: samples[samples.len() - 1],
+    }
+}
+
+/// Times parsing and each part separately, `runs` times each.
+pub fn bench_day(solution: &Solution, input: &[String], runs: usize) -> Result<DayTimings, String> {
+    let puzzle = (solution.parse)(input)?;
+    Ok(DayTimings {
+        day: solution.day,
+        parse: time(runs, || (solution.parse)(input)),
+        part1: time(runs, || puzzle.part1()),
+        part2: time(runs, || puzzle.part2()),
+    })
+}
+
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs_f64();
+    if secs >= 1.0 {
+        format!("{:.2}s", secs)
+    } else if secs >= 1e-3 {
+        format!("{:.2}ms", secs * 1e3)
+    } else {
+        format!("{:.1}µs", secs * 1e6)
+    }
+}
+
+pub fn print_day(timings: &DayTimings) {
+    println!("day {}", timings.day);
+    println!("{:<8} {:>12} {:>12} {:>12}", "", "min", "median", "max");
+    for (name, timing) in [("parse", &timings.parse), ("part 1", &timings.part1), ("part 2", &timings.part2)] {
+        println!(
+            "{:<8} {:>12} {:>12} {:>12}",
+            name,
+            format_duration(timing.min),
+            format_duration(timing.median),
+            format_duration(timing.max)
+        );
+    }
+}
+
+/// One row per day with median times, plus the year's total.
+pub fn print_year(all: &[DayTimings]) {
+    println!("{:<5} {:>12} {:>12} {:>12} {:>12}", "day", "parse", "part 1", "part 2", "total");
+    let mut year = Duration::ZERO;
+    for timings in all {
+        let total = timings.parse.median + timings.part1.median + timings.part2.median;
+        year += total;
+        println!(
+            "{:<5} {:>12} {:>12} {:>12} {:>12}",
+            timings.day,
+            format_duration(timings.parse.median),
+            format_duration(timings.part1.median),
+            format_duration(timings.part2.median),
+            format_duration(total)
+        );
+    }
+    println!("{:<5} {:>12} {:>12} {:>12} {:>12}", "all", "", "", "", format_duration(year));
+}
@@ -1,145 +1,869 @@
-use crate::file_input;
-use std::io::Write;
+use std::ops::Range;
+use std::sync::OnceLock;
 
-const WIDTH: i32 = 101;
-const HEIGHT: i32 = 103;
+use regex::Regex;
+
+use crate::geometry::{Point, Vec2};
+use crate::grid::Grid;
+use crate::image::{self, Image, Rgb};
+use crate::math;
+use crate::solver::{Answer, Solver};
+use crate::torus::{Particle, Torus};
+
+pub use crate::torus::Collision;
+
+pub const WIDTH: i32 = 101;
+pub const HEIGHT: i32 = 103;
+pub const SAFETY_TICKS: i32 = 100;
+
+const ROBOT_COLOUR: Rgb = [40, 200, 60];
+const SHEET_BACKGROUND: Rgb = [60, 60, 60];
+const SHEET_PADDING: usize = 2;
+/// The longest room side `Room::parse` accepts; every tile of the room gets a
+/// counter, so this keeps rooms to a few million tiles.
+pub const MAX_ROOM_SIDE: i32 = 4096;
+
+/// The size of the room the robots move in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Room {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Room {
+    pub const PUZZLE: Room = Room { width: WIDTH, height: HEIGHT };
+    pub const EXAMPLE: Room = Room { width: 11, height: 7 };
+
+    /// The puzzle's worked example uses an 11x7 room; anything whose robots
+    /// all start inside that is taken to be the example.
+    pub fn infer(robots: &[Robot]) -> Room {
+        let fits_example = robots
+            .iter()
+            .all(|r| r.init_pos.x < Room::EXAMPLE.width && r.init_pos.y < Room::EXAMPLE.height);
+        if fits_example && !robots.is_empty() { Room::EXAMPLE } else { Room::PUZZLE }
+    }
+
+    /// The room as a torus; robots walking off one edge come back on the other.
+    pub fn torus(&self) -> Torus<2> {
+        Torus { size: [self.width as i64, self.height as i64] }
+    }
+
+    /// Every robot is back where it started after this many ticks, since each
+    /// coordinate repeats with the room's width or height.
+    pub fn period(&self) -> i64 {
+        self.torus().period()
+    }
+
+    /// One full period of ticks; every distinct arrangement appears in it.
+    pub fn ticks(&self) -> Range<i64> {
+        0..self.period()
+    }
+
+    /// Parses `WIDTHxHEIGHT`, e.g. `11x7`, with each side at most `MAX_ROOM_SIDE`.
+    pub fn parse(value: &str) -> Result<Room, String> {
+        let invalid = || format!("invalid room size {}, expected WIDTHxHEIGHT", value);
+        let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+        let width = width.parse().map_err(|_| invalid())?;
+        let height = height.parse().map_err(|_| invalid())?;
+        if width <= 0 || height <= 0 {
+            return Err(invalid());
+        }
+        if width > MAX_ROOM_SIDE || height > MAX_ROOM_SIDE {
+            return Err(format!("room size {} is too large, sides can be at most {}", value, MAX_ROOM_SIDE));
+        }
+        Ok(Room { width, height })
+    }
+}
+
+/// Parses one robot per line, e.g. `p=0,4 v=3,-3`. Blank lines are skipped;
+/// anything else that does not match is reported with its line number.
+pub fn get_robots(input: &[String]) -> Result<Vec<Robot>, String> {
+    static ROBOT: OnceLock<Regex> = OnceLock::new();
+    let re = ROBOT.get_or_init(|| Regex::new(r"^p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)$").unwrap());
 
-fn get_robots() -> Vec<Robot> {
-    let input: Vec<String> = file_input::read_input();
     let mut robots: Vec<Robot> = Vec::new();
-    for line in input {
-        // p=0,4 v=3,-3
-        let re = regex::Regex::new(r"p=(\d+),(\d+) v=(-?\d+),(-?\d+)").unwrap();
-        let caps = re.captures(&line).unwrap();
+    for (i, line) in input.iter().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || format!("line {}: invalid robot {}, expected p=X,Y v=DX,DY", i + 1, line);
+        let caps = re.captures(line).ok_or_else(invalid)?;
+        let number = |n: usize| caps[n].parse::<i32>().map_err(|_| invalid());
 
-        let pos = vec![&caps[1], &caps[2]];
-        let vel = vec![&caps[3], &caps[4]];
+        let init_pos = Point { x: number(1)?, y: number(2)? };
+        let direction = Vec2 { x: number(3)?, y: number(4)? };
+        robots.push(Robot { init_pos, direction });
+    }
+    Ok(robots)
+}
 
-        let init_pos = Point {
-            x: pos[0].parse().unwrap(),
-            y: pos[1].parse().unwrap(),
-        };
+pub fn get_position_after_times(robot: &Robot, times: i32, room: Room) -> Point {
+    let [x, y] = room.torus().position(&robot.particle(), times as i64);
+    Point::new(x as i32, y as i32)
+}
 
-        let direction = Point {
-            x: vel[0].parse().unwrap(),
-            y: vel[1].parse().unwrap(),
-        };
+/// When `a` and `b` share a tile, as `(first, every)`, or `None` if never.
+pub fn collision_ticks(a: &Robot, b: &Robot, room: Room) -> Option<(i64, i64)> {
+    room.torus().collision(&a.particle(), &b.particle())
+}
 
-        let robot = Robot {
-            init_pos: init_pos,
-            direction: direction,
-        };
-        robots.push(robot);
+/// When `robot` stands on `p`, as `(first, every)`, or `None` if never.
+pub fn arrival_ticks(robot: &Robot, p: Point, room: Room) -> Option<(i64, i64)> {
+    room.torus().arrival(&robot.particle(), [p.x as i64, p.y as i64])
+}
+
+/// Every pair of robots that ever coincides.
+pub fn collisions(robots: &[Robot], room: Room) -> Vec<Collision> {
+    room.torus().collisions(&robots.iter().map(Robot::particle).collect::<Vec<_>>())
+}
+
+/// How many robots stand on each tile after `times` ticks.
+pub fn room_after_times(robots: &[Robot], times: i32, room: Room) -> Grid<i32> {
+    let mut counts = Grid::new(room.width as usize, room.height as usize, 0);
+    for robot in robots {
+        counts[get_position_after_times(robot, times, room)] += 1;
+    }
+    counts
+}
+
+pub fn print_room(room: &Grid<i32>) -> String {
+    room.render(|_, count| if *count > 0 { char::from_digit(*count as u32, 10).unwrap_or('+') } else { '.' })
+}
+
+/// Draws a room with each tile `scale` pixels square, lit where robots stand.
+pub fn room_image(room: &Grid<i32>, scale: usize) -> Image {
+    Image::from_grid(room, scale, |count| if *count > 0 { ROBOT_COLOUR } else { image::BLACK })
+}
+
+/// Tiles the room at each of `ticks` into one image, `columns` frames to a
+/// row, with each frame's tick written above it.
+pub fn contact_sheet(robots: &[Robot], room: Room, ticks: &[i64], columns: usize, scale: usize) -> Image {
+    let columns = columns.clamp(1, ticks.len().max(1));
+    let rows = ticks.len().div_ceil(columns);
+    let label_height = image::GLYPH_HEIGHT * scale + SHEET_PADDING;
+    let frame_width = room.width as usize * scale;
+    let frame_height = label_height + room.height as usize * scale;
+
+    let mut sheet = Image::new(
+        columns * (frame_width + SHEET_PADDING) + SHEET_PADDING,
+        rows * (frame_height + SHEET_PADDING) + SHEET_PADDING,
+        SHEET_BACKGROUND,
+    );
+    for (i, tick) in ticks.iter().enumerate() {
+        let x = SHEET_PADDING + (i % columns) * (frame_width + SHEET_PADDING);
+        let y = SHEET_PADDING + (i / columns) * (frame_height + SHEET_PADDING);
+        sheet.draw_text(&tick.to_string(), x, y, scale, image::WHITE);
+        let frame = room_image(&room_after_times(robots, tick.rem_euclid(room.period()) as i32, room), scale);
+        sheet.blit(&frame, x, y + label_height);
     }
-    robots
+    sheet
 }
 
-fn get_position_after_times(robot: &Robot, times: i32)-> Point {
-    let x = (robot.init_pos.x + robot.direction.x * times) % WIDTH;
-    let y = (robot.init_pos.y + robot.direction.y * times) % HEIGHT; 
-    Point {
-        x: if x < 0 { x + WIDTH } else { x },
-        y: if y < 0 { y + HEIGHT } else { y },
+/// Parses a comma-separated list of ticks and `start..end` ranges, e.g.
+/// `0..100,7153`. A range may take a step: `start..end:step`.
+pub fn parse_ticks(spec: &str) -> Result<Vec<i64>, String> {
+    let invalid = |part: &str| format!("invalid ticks {}, expected N, A..B or A..B:STEP", part);
+    let mut ticks = Vec::new();
+    for part in spec.split(',') {
+        let number = |s: &str| s.trim().parse::<i64>().map_err(|_| invalid(part));
+        match part.split_once("..") {
+            Some((start, rest)) => {
+                let (end, step) = match rest.split_once(':') {
+                    Some((end, step)) => (end, number(step)?),
+                    None => (rest, 1),
+                };
+                if step <= 0 {
+                    return Err(invalid(part));
+                }
+                ticks.extend((number(start)?..number(end)?).step_by(step as usize));
+            }
+            None => ticks.push(number(part)?),
+        }
     }
+    Ok(ticks)
 }
 
-fn print_room(room: &Vec<Vec<i32>>, file_name: &str) {
-    let mut file = std::fs::File::create(file_name).unwrap();
-    for i in 0..room.len() {
-        for j in 0..room[i].len() {
-            let n = if room[i][j] > 0 { room[i][j].to_string() } else { ".".to_string() };
-            write!(file, "{}", n).unwrap();
+/// Robots in the top-left, top-right, bottom-left and bottom-right quadrants
+/// after `times` ticks. Robots on the middle row or column are in none.
+pub fn quadrant_counts(robots: &[Robot], times: i32, room: Room) -> [usize; 4] {
+    let mut counts = [0; 4];
+    for robot in robots {
+        if let Some(q) = quadrant(get_position_after_times(robot, times, room), room) {
+            counts[q] += 1;
         }
-        write!(file, "\n").unwrap();
     }
+    counts
 }
 
-fn small_differece(a: i32, b: i32) -> bool {
-    (a - b).abs() < 2
+/// Which quadrant `point` is in, numbered as in `quadrant_counts`.
+fn quadrant(point: Point, room: Room) -> Option<usize> {
+    let (mid_x, mid_y) = (room.width / 2, room.height / 2);
+    if (room.width % 2 == 1 && point.x == mid_x) || (room.height % 2 == 1 && point.y == mid_y) {
+        return None;
+    }
+    let right = (point.x >= room.width - mid_x) as usize;
+    let bottom = (point.y >= room.height - mid_y) as usize;
+    Some(bottom * 2 + right)
 }
 
-pub fn solve() {
-    let robots = get_robots();
+pub fn safety_factor(robots: &[Robot], times: i32, room: Room) -> usize {
+    quadrant_counts(robots, times, room).iter().product()
+}
 
-    for times in 0..10000 {
-        let mut top_corner_bots = 0;
-        let mut trunk_bots = 0;
-        let mut q1_bots = 0;
-        let mut q2_bots = 0;
-        let mut q3_bots = 0;
-        let mut q4_bots = 0;
-    
-        let mut room: Vec<Vec<i32>> = vec![vec![0; WIDTH as usize]; HEIGHT as usize];    
-    
-        for i in 0..robots.len() {
-            let robot = &robots[i];
-            let point = get_position_after_times(robot, times);
-    
-            room[point.y as usize][point.x as usize] += 1;
-    
-            if point.x + point.y < WIDTH / 4 {
-                top_corner_bots += 1;
-            } else if WIDTH - point.x + point.y < WIDTH / 4 {
-                top_corner_bots += 1;   
+/// `n²` times the variance of `values`, which keeps it in exact integers.
+fn scaled_variance(values: impl Iterator<Item = i64>) -> i64 {
+    let (mut n, mut sum, mut sum_sq) = (0, 0, 0);
+    for v in values {
+        n += 1;
+        sum += v;
+        sum_sq += v * v;
+    }
+    n * sum_sq - sum * sum
+}
+
+/// The tick in `0..size` at which one coordinate of the robots is most tightly
+/// clustered. That coordinate repeats every `size` ticks, so one cycle is enough.
+fn tightest_tick(robots: &[Robot], size: i32, coordinate: impl Fn(&Robot) -> (i32, i32)) -> i64 {
+    (0..size)
+        .min_by_key(|t| {
+            scaled_variance(robots.iter().map(|r| {
+                let (p, v) = coordinate(r);
+                (p as i64 + v.rem_euclid(size) as i64 * *t as i64).rem_euclid(size as i64)
+            }))
+        })
+        .unwrap_or(0) as i64
+}
+
+/// Finds the tick at which the robots draw the picture. The picture packs most
+/// robots into a small area, so the x variance is smallest at that tick modulo
+/// the width and the y variance smallest modulo the height; the two residues
+/// pin down the tick within the room's period.
+pub fn find_tree(robots: &[Robot], room: Room) -> Option<i64> {
+    let tx = tightest_tick(robots, room.width, |r| (r.init_pos.x, r.direction.x));
+    let ty = tightest_tick(robots, room.height, |r| (r.init_pos.y, r.direction.y));
+    math::crt(tx, room.width as i64, ty, room.height as i64).map(|(tick, _)| tick)
+}
+
+pub fn longest_line(room: &Grid<i32>) -> i32 {
+    let mut longest_line = 0;
+    for row in room.rows() {
+        let mut line = 0;
+        for count in row {
+            if *count > 0 {
+                line += 1;
+                longest_line = longest_line.max(line);
+            } else {
+                line = 0;
             }
+        }
+    }
+    longest_line
+}
+
+/// All robots at one tick, stored as flat coordinate arrays so a whole period
+/// can be stepped through quickly. Each step moves every robot by one tick and
+/// updates a per-tile robot count in place rather than rebuilding the room.
+pub struct Swarm {
+    room: Room,
+    tick: i64,
+    xs: Vec<i32>,
+    ys: Vec<i32>,
+    /// Velocities reduced to `0..width` and `0..height`, so a step never
+    /// needs more than one subtraction to wrap.
+    dxs: Vec<i32>,
+    dys: Vec<i32>,
+    occupancy: Vec<u16>,
+    distinct: usize,
+}
+
+impl Swarm {
+    pub fn new(robots: &[Robot], room: Room) -> Swarm {
+        let mut swarm = Swarm {
+            room,
+            tick: 0,
+            xs: robots.iter().map(|r| r.init_pos.x.rem_euclid(room.width)).collect(),
+            ys: robots.iter().map(|r| r.init_pos.y.rem_euclid(room.height)).collect(),
+            dxs: robots.iter().map(|r| r.direction.x.rem_euclid(room.width)).collect(),
+            dys: robots.iter().map(|r| r.direction.y.rem_euclid(room.height)).collect(),
+            occupancy: vec![0; (room.width * room.height) as usize],
+            distinct: 0,
+        };
+        swarm.fill_occupancy();
+        swarm
+    }
 
-            if point.x > WIDTH / 2 - 10 && point.x < WIDTH / 2 + 10
-            && point.y > HEIGHT - 10 {
-                trunk_bots += 1;
+    pub fn tick(&self) -> i64 {
+        self.tick
+    }
+
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+
+    fn cell(&self, i: usize) -> usize {
+        (self.ys[i] * self.room.width + self.xs[i]) as usize
+    }
+
+    fn fill_occupancy(&mut self) {
+        self.occupancy.fill(0);
+        self.distinct = 0;
+        for i in 0..self.len() {
+            self.occupy(i);
+        }
+    }
+
+    fn occupy(&mut self, i: usize) {
+        let cell = self.cell(i);
+        self.distinct += (self.occupancy[cell] == 0) as usize;
+        self.occupancy[cell] += 1;
+    }
+
+    fn vacate(&mut self, i: usize) {
+        let cell = self.cell(i);
+        self.occupancy[cell] -= 1;
+        self.distinct -= (self.occupancy[cell] == 0) as usize;
+    }
+
+    /// Moves every robot forward one tick.
+    pub fn step(&mut self) {
+        for i in 0..self.len() {
+            self.vacate(i);
+        }
+        let (width, height) = (self.room.width, self.room.height);
+        for (x, dx) in self.xs.iter_mut().zip(&self.dxs) {
+            *x += dx;
+            if *x >= width {
+                *x -= width;
+            }
+        }
+        for (y, dy) in self.ys.iter_mut().zip(&self.dys) {
+            *y += dy;
+            if *y >= height {
+                *y -= height;
             }
+        }
+        for i in 0..self.len() {
+            self.occupy(i);
+        }
+        self.tick += 1;
+    }
+
+    /// Jumps straight to `tick`, computing every position from scratch.
+    pub fn seek(&mut self, tick: i64) {
+        let delta = tick - self.tick;
+        let (width, height) = (self.room.width as i64, self.room.height as i64);
+        for (x, dx) in self.xs.iter_mut().zip(&self.dxs) {
+            *x = (*x as i64 + *dx as i64 * delta.rem_euclid(width)).rem_euclid(width) as i32;
+        }
+        for (y, dy) in self.ys.iter_mut().zip(&self.dys) {
+            *y = (*y as i64 + *dy as i64 * delta.rem_euclid(height)).rem_euclid(height) as i32;
+        }
+        self.tick = tick;
+        self.fill_occupancy();
+    }
+
+    /// Steps when `tick` is the next one and seeks otherwise.
+    pub fn advance_to(&mut self, tick: i64) {
+        if tick == self.tick + 1 {
+            self.step();
+        } else if tick != self.tick {
+            self.seek(tick);
+        }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Point> + '_ {
+        self.xs.iter().zip(&self.ys).map(|(x, y)| Point::new(*x, *y))
+    }
 
-            if point.x < WIDTH/2 && point.y < HEIGHT/2 {
-                q1_bots += 1;
-            } else if point.x > WIDTH/2 && point.y < HEIGHT/2 {
-                q2_bots += 1;
-            } else if point.x < WIDTH/2 && point.y > HEIGHT/2 {
-                q3_bots += 1;
-            } else if point.x > WIDTH/2 && point.y > HEIGHT/2 {
-                q4_bots += 1;
+    /// How many robots stand on `p`.
+    pub fn count_at(&self, p: Point) -> u16 {
+        self.occupancy[(p.y * self.room.width + p.x) as usize]
+    }
+
+    /// How many tiles have at least one robot on them.
+    pub fn distinct_cells(&self) -> usize {
+        self.distinct
+    }
+
+    pub fn longest_line(&self) -> i32 {
+        let mut longest = 0;
+        for row in self.occupancy.chunks(self.room.width as usize) {
+            let mut line = 0;
+            for count in row {
+                line = if *count > 0 { line + 1 } else { 0 };
+                longest = longest.max(line);
             }
         }
+        longest
+    }
 
-        let mut longest_line = 0;
-        for i in 0..HEIGHT {
-            for j in 0..WIDTH {
-                if room[i as usize][j as usize] > 0 {
-                    let mut line = 0;
-                    for k in j..WIDTH {
-                        if room[i as usize][k as usize] > 0 {
-                            line += 1;
-                        } else {
-                            break;
-                        }
-                    }
-                    if line > longest_line {
-                        longest_line = line;
-                    }
-                }
+    pub fn to_grid(&self) -> Grid<i32> {
+        let mut counts = Grid::new(self.room.width as usize, self.room.height as usize, 0);
+        for p in self.positions() {
+            counts[p] += 1;
+        }
+        counts
+    }
+
+    pub fn stats(&self) -> TickStats {
+        let mut quadrants = [0; 4];
+        for p in self.positions() {
+            if let Some(q) = quadrant(p, self.room) {
+                quadrants[q] += 1;
             }
         }
+        let n = (self.len() as f64).max(1.0);
+        let variance = |values: &[i32]| scaled_variance(values.iter().map(|v| *v as i64)) as f64 / (n * n);
 
-        if longest_line > 10 {
-            println!("corner bots: {}, Times: {}", top_corner_bots, times);
-            let file_name = format!("{}-timesLongLine.txt", times);
-            print_room(&room, &file_name);
+        TickStats {
+            tick: self.tick,
+            quadrants,
+            max_occupancy: self.occupancy.iter().max().copied().unwrap_or(0) as i32,
+            longest_line: self.longest_line(),
+            variance_x: variance(&self.xs),
+            variance_y: variance(&self.ys),
+            distinct_cells: self.distinct,
         }
+    }
+}
 
-        if top_corner_bots < 11 {
-            println!("corner bots: {}, Times: {}", top_corner_bots, times);
-            let file_name = format!("{}-times.txt", times);
-            print_room(&room, &file_name);
+/// The first tick in the room's period at which no two robots share a tile.
+pub fn first_tick_without_overlap(robots: &[Robot], room: Room) -> Option<i64> {
+    let mut swarm = Swarm::new(robots, room);
+    for tick in room.ticks() {
+        swarm.advance_to(tick);
+        if swarm.distinct_cells() == swarm.len() {
+            return Some(tick);
         }
     }
+    None
+}
 
-    
+/// How part 2 looks for the picture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeSearch {
+    /// Solve for the tick from the tightest x and y spreads; see `find_tree`.
+    Variance,
+    /// Step a `Swarm` through the whole period for the first tick at which
+    /// every robot stands alone.
+    Overlap,
 }
 
-struct Point {
-    x: i32,
-    y: i32,
+impl TreeSearch {
+    pub fn parse(text: &str) -> Result<TreeSearch, String> {
+        match text {
+            "variance" => Ok(TreeSearch::Variance),
+            "overlap" => Ok(TreeSearch::Overlap),
+            _ => Err(format!("invalid tree search {} (expected variance or overlap)", text)),
+        }
+    }
+}
+
+/// Measures of how the robots are spread out at one tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickStats {
+    pub tick: i64,
+    pub quadrants: [usize; 4],
+    pub max_occupancy: i32,
+    pub longest_line: i32,
+    pub variance_x: f64,
+    pub variance_y: f64,
+    pub distinct_cells: usize,
 }
 
-struct Robot {
-    init_pos: Point,
-    direction: Point,
-}
\ No newline at end of file
+pub const STATS_HEADER: &str =
+    "tick,top_left,top_right,bottom_left,bottom_right,max_occupancy,longest_line,variance_x,variance_y,distinct_cells";
+
+impl TickStats {
+    pub fn csv_row(&self) -> String {
+        let [tl, tr, bl, br] = self.quadrants;
+        format!(
+            "{},{},{},{},{},{},{},{:.3},{:.3},{}",
+            self.tick,
+            tl,
+            tr,
+            bl,
+            br,
+            self.max_occupancy,
+            self.longest_line,
+            self.variance_x,
+            self.variance_y,
+            self.distinct_cells
+        )
+    }
+}
+
+pub fn tick_stats(robots: &[Robot], tick: i64, room: Room) -> TickStats {
+    let mut swarm = Swarm::new(robots, room);
+    swarm.seek(tick);
+    swarm.stats()
+}
+
+/// The statistics for each of `ticks` as CSV, with a header line.
+pub fn stats_csv(robots: &[Robot], ticks: impl Iterator<Item = i64>, room: Room) -> String {
+    let mut swarm = Swarm::new(robots, room);
+    let mut csv = format!("{}\n", STATS_HEADER);
+    for tick in ticks {
+        swarm.advance_to(tick);
+        csv.push_str(&swarm.stats().csv_row());
+        csv.push('\n');
+    }
+    csv
+}
+
+pub struct Day14 {
+    pub robots: Vec<Robot>,
+    pub room: Room,
+    /// The tick part 1 measures the safety factor at.
+    pub safety_ticks: i32,
+    pub tree_search: TreeSearch,
+}
+
+impl Solver for Day14 {
+    fn parse(input: &[String]) -> Result<Self, String> {
+        let robots = get_robots(input)?;
+        let room = Room::infer(&robots);
+        Ok(Day14 { robots, room, safety_ticks: SAFETY_TICKS, tree_search: TreeSearch::Variance })
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "room" => self.room = Room::parse(value)?,
+            "ticks" => self.safety_ticks = value.parse().map_err(|_| format!("invalid tick count {}", value))?,
+            "tree" => self.tree_search = TreeSearch::parse(value)?,
+            _ => return Err(format!("unknown option {} for day 14 (expected room, ticks or tree)", name)),
+        }
+        Ok(())
+    }
+
+    fn part1(&self) -> Answer {
+        Answer::from(safety_factor(&self.robots, self.safety_ticks, self.room))
+    }
+
+    fn part2(&self) -> Answer {
+        let tick = match self.tree_search {
+            TreeSearch::Variance => find_tree(&self.robots, self.room),
+            TreeSearch::Overlap => first_tick_without_overlap(&self.robots, self.room),
+        };
+        tick.map_or(Answer::Unsolved, Answer::from)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Robot {
+    pub init_pos: Point,
+    pub direction: Vec2,
+}
+
+impl Robot {
+    pub fn particle(&self) -> Particle<2> {
+        Particle {
+            position: [self.init_pos.x as i64, self.init_pos.y as i64],
+            velocity: [self.direction.x as i64, self.direction.y as i64],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_input::read_lines;
+
+    const EXAMPLE: &str = include_str!("../inputs/day14-example.txt");
+
+    fn example() -> Vec<String> {
+        read_lines(EXAMPLE.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn parses_example_robots() {
+        let robots = get_robots(&example()).unwrap();
+        assert_eq!(robots.len(), 12);
+        assert_eq!(robots[0].init_pos, Point::new(0, 4));
+        assert_eq!(robots[0].direction, Vec2::new(3, -3));
+    }
+
+    #[test]
+    fn rejects_malformed_robot() {
+        let input = vec!["p=1,2 v=3,4".to_string(), "p=1,2 v=3".to_string()];
+        assert_eq!(get_robots(&input).unwrap_err(), "line 2: invalid robot p=1,2 v=3, expected p=X,Y v=DX,DY");
+        let input = vec!["p=1,2 v=3,4 extra".to_string()];
+        assert!(get_robots(&input).unwrap_err().starts_with("line 1:"));
+        let input = vec!["p=99999999999,2 v=3,4".to_string()];
+        assert!(get_robots(&input).unwrap_err().starts_with("line 1:"));
+    }
+
+    #[test]
+    fn accepts_signed_positions_and_blank_lines() {
+        let input = vec!["p=-1,3 v=2,-2".to_string(), "".to_string(), " p=4,-5 v=0,0 ".to_string()];
+        let robots = get_robots(&input).unwrap();
+        assert_eq!(robots.len(), 2);
+        assert_eq!(robots[0].init_pos, Point::new(-1, 3));
+        assert_eq!(robots[1].init_pos, Point::new(4, -5));
+        assert_eq!(get_position_after_times(&robots[0], 0, Room::EXAMPLE), Point::new(10, 3));
+    }
+
+    #[test]
+    fn wraps_around_the_room() {
+        let robot = Robot { init_pos: Point::new(2, 4), direction: Vec2::new(2, -3) };
+        assert_eq!(get_position_after_times(&robot, 5, Room::PUZZLE), Point::new(12, HEIGHT - 11));
+        assert_eq!(get_position_after_times(&robot, 5, Room::EXAMPLE), Point::new(1, 3));
+    }
+
+    #[test]
+    fn counts_quadrants_without_the_middle() {
+        let still = |x, y| Robot { init_pos: Point::new(x, y), direction: Vec2::new(0, 0) };
+        let robots = vec![
+            still(0, 0),
+            still(WIDTH - 1, 0),
+            still(WIDTH - 1, 1),
+            still(0, HEIGHT - 1),
+            still(WIDTH - 1, HEIGHT - 1),
+            still(WIDTH / 2, 0),
+            still(0, HEIGHT / 2),
+        ];
+        assert_eq!(quadrant_counts(&robots, SAFETY_TICKS, Room::PUZZLE), [1, 2, 1, 1]);
+        assert_eq!(safety_factor(&robots, SAFETY_TICKS, Room::PUZZLE), 2);
+    }
+
+    #[test]
+    fn moves_robots_into_quadrants_over_time() {
+        let robot = Robot { init_pos: Point::new(WIDTH / 2, HEIGHT / 2), direction: Vec2::new(1, 1) };
+        assert_eq!(quadrant_counts(std::slice::from_ref(&robot), 0, Room::PUZZLE), [0, 0, 0, 0]);
+        assert_eq!(quadrant_counts(std::slice::from_ref(&robot), 1, Room::PUZZLE), [0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn infers_the_example_room() {
+        let day = Day14::parse(&example()).unwrap();
+        assert_eq!(day.room, Room::EXAMPLE);
+        assert_eq!(day.part1(), Answer::Int(12));
+    }
+
+    #[test]
+    fn room_can_be_overridden() {
+        let mut day = Day14::parse(&example()).unwrap();
+        day.set_option("room", "101x103").unwrap();
+        assert_eq!(day.room, Room::PUZZLE);
+        assert!(day.set_option("room", "11by7").is_err());
+        assert!(day.set_option("room", "70000x70000").is_err());
+        assert!(day.set_option("room", "4096x1").is_ok());
+        assert!(day.set_option("colour", "red").is_err());
+    }
+
+    #[test]
+    fn finds_the_tick_robots_cluster_at() {
+        // Scatter robots with pseudo-random velocities so that they all land
+        // in a 10x10 square at the chosen tick.
+        let tick = 7_153;
+        let mut seed: i64 = 17;
+        let mut next = |n: i64| {
+            seed = (seed * 1_103_515_245 + 12_345) % 2_147_483_648;
+            (seed / 65_536) % n
+        };
+        let robots = (0..300)
+            .map(|_| {
+                let target = Point::new(45 + next(10) as i32, 40 + next(10) as i32);
+                let direction = Vec2::new(next(201) as i32 - 100, next(201) as i32 - 100);
+                let init_pos = (target - direction * tick).wrap(WIDTH, HEIGHT);
+                Robot { init_pos, direction }
+            })
+            .collect::<Vec<Robot>>();
+
+        assert_eq!(find_tree(&robots, Room::PUZZLE), Some(tick as i64));
+        let day = Day14 { robots, room: Room::PUZZLE, safety_ticks: SAFETY_TICKS, tree_search: TreeSearch::Variance };
+        assert_eq!(day.part2(), Answer::Int(tick as i64));
+    }
+
+    #[test]
+    fn large_velocities_do_not_overflow() {
+        let robots = get_robots(&["p=5,5 v=100000000,3".to_string(), "p=-7,2 v=-2000000000,2147483647".to_string()]).unwrap();
+        let reduced = robots
+            .iter()
+            .map(|r| Robot {
+                init_pos: r.init_pos.wrap(WIDTH, HEIGHT),
+                direction: Vec2::new(r.direction.x.rem_euclid(WIDTH), r.direction.y.rem_euclid(HEIGHT)),
+            })
+            .collect::<Vec<Robot>>();
+        assert_eq!(find_tree(&robots, Room::PUZZLE), find_tree(&reduced, Room::PUZZLE));
+    }
+
+    #[test]
+    fn period_is_lcm_of_room_sides() {
+        assert_eq!(Room::PUZZLE.period(), 10_403);
+        assert_eq!(Room::EXAMPLE.ticks(), 0..77);
+        assert_eq!(Room { width: 4, height: 6 }.period(), 12);
+    }
+
+    #[test]
+    fn positions_repeat_after_the_period() {
+        let room = Room::EXAMPLE;
+        for robot in get_robots(&example()).unwrap() {
+            let period = room.period() as i32;
+            assert_eq!(get_position_after_times(&robot, period, room), robot.init_pos);
+            assert_eq!(get_position_after_times(&robot, period + 3, room), get_position_after_times(&robot, 3, room));
+        }
+    }
+
+    #[test]
+    fn finds_collision_ticks() {
+        let room = Room::EXAMPLE;
+        let mover = Robot { init_pos: Point::new(0, 0), direction: Vec2::new(1, 0) };
+        let still = Robot { init_pos: Point::new(5, 0), direction: Vec2::new(0, 0) };
+        assert_eq!(collision_ticks(&mover, &still, room), Some((5, 11)));
+
+        let parallel = Robot { init_pos: Point::new(0, 1), direction: Vec2::new(1, 0) };
+        assert_eq!(collision_ticks(&mover, &parallel, room), None);
+    }
+
+    #[test]
+    fn finds_when_a_robot_reaches_a_tile() {
+        let room = Room::EXAMPLE;
+        let robot = Robot { init_pos: Point::new(2, 4), direction: Vec2::new(2, -3) };
+        let (first, every) = arrival_ticks(&robot, Point::new(1, 3), room).unwrap();
+        assert_eq!((first, every), (5, 77));
+        assert_eq!(get_position_after_times(&robot, first as i32, room), Point::new(1, 3));
+    }
+
+    #[test]
+    fn collisions_match_simulation() {
+        let room = Room::EXAMPLE;
+        let robots = get_robots(&example()).unwrap();
+        let found = collisions(&robots, room);
+        for i in 0..robots.len() {
+            for j in i + 1..robots.len() {
+                let simulated = room
+                    .ticks()
+                    .filter(|t| {
+                        get_position_after_times(&robots[i], *t as i32, room)
+                            == get_position_after_times(&robots[j], *t as i32, room)
+                    })
+                    .collect::<Vec<i64>>();
+                let predicted = found
+                    .iter()
+                    .find(|c| c.particles == (i, j))
+                    .map(|c| (c.first..room.period()).step_by(c.every as usize).collect::<Vec<i64>>())
+                    .unwrap_or_default();
+                assert_eq!(predicted, simulated, "robots {} and {}", i, j);
+            }
+        }
+        assert!(!found.is_empty());
+    }
+
+    #[test]
+    fn finds_longest_line() {
+        let room = Grid::from_rows(vec![vec![0, 1, 1, 0, 1], vec![1, 1, 2, 1, 0]]).unwrap();
+        assert_eq!(longest_line(&room), 4);
+        assert_eq!(print_room(&room), ".11.1\n1121.\n");
+    }
+
+    #[test]
+    fn computes_tick_stats() {
+        let robot = |x, y| Robot { init_pos: Point::new(x, y), direction: Vec2::new(1, 0) };
+        let robots = vec![robot(0, 0), robot(1, 0), robot(1, 0), robot(9, 6)];
+        let stats = tick_stats(&robots, 1, Room::EXAMPLE);
+        assert_eq!(stats.quadrants, [3, 0, 0, 1]);
+        assert_eq!(stats.max_occupancy, 2);
+        assert_eq!(stats.longest_line, 2);
+        assert_eq!(stats.distinct_cells, 3);
+        // x is 1, 2, 2 and 10; y is 0, 0, 0 and 6.
+        assert_eq!(stats.variance_x, 13.1875);
+        assert_eq!(stats.variance_y, 6.75);
+        assert_eq!(stats.csv_row(), "1,3,0,0,1,2,2,13.188,6.750,3");
+    }
+
+    #[test]
+    fn writes_stats_for_a_period() {
+        let robots = get_robots(&example()).unwrap();
+        let csv = stats_csv(&robots, Room::EXAMPLE.ticks(), Room::EXAMPLE);
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 78);
+        assert_eq!(lines[0], STATS_HEADER);
+        assert!(lines[101 % 77 + 1].starts_with("24,"));
+        let at_100 = tick_stats(&robots, 100, Room::EXAMPLE);
+        assert_eq!(at_100.quadrants.iter().product::<usize>(), 12);
+    }
+
+    #[test]
+    fn swarm_steps_match_closed_form() {
+        let room = Room::EXAMPLE;
+        let robots = get_robots(&example()).unwrap();
+        let mut swarm = Swarm::new(&robots, room);
+        for tick in 1..=room.period() + 3 {
+            swarm.step();
+            let expected = robots
+                .iter()
+                .map(|r| get_position_after_times(r, tick as i32, room))
+                .collect::<Vec<Point>>();
+            assert_eq!(swarm.positions().collect::<Vec<Point>>(), expected, "tick {}", tick);
+            let counts = room_after_times(&robots, tick as i32, room);
+            assert_eq!(swarm.to_grid(), counts);
+            assert_eq!(swarm.distinct_cells(), counts.iter().filter(|(_, c)| **c > 0).count());
+            assert_eq!(swarm.longest_line(), longest_line(&counts));
+        }
+        assert_eq!(swarm.tick(), 80);
+    }
+
+    #[test]
+    fn swarm_seeks_backwards_and_forwards() {
+        let room = Room::EXAMPLE;
+        let robots = get_robots(&example()).unwrap();
+        let mut swarm = Swarm::new(&robots, room);
+        swarm.seek(100);
+        assert_eq!(swarm.stats().quadrants.iter().product::<usize>(), 12);
+        swarm.advance_to(-3);
+        let expected = room_after_times(&robots, room.period() as i32 - 3, room);
+        assert_eq!(swarm.to_grid(), expected);
+        assert_eq!(swarm.count_at(Point::new(0, 0)), expected[Point::new(0, 0)] as u16);
+    }
+
+    #[test]
+    fn finds_first_tick_without_overlap() {
+        let room = Room::EXAMPLE;
+        let robots = vec![
+            Robot { init_pos: Point::new(0, 0), direction: Vec2::new(1, 0) },
+            Robot { init_pos: Point::new(0, 0), direction: Vec2::new(0, 1) },
+        ];
+        assert_eq!(first_tick_without_overlap(&robots, room), Some(1));
+        let stacked = vec![robots[0], robots[0]];
+        assert_eq!(first_tick_without_overlap(&stacked, room), None);
+
+        let mut day = Day14 { robots, room, safety_ticks: SAFETY_TICKS, tree_search: TreeSearch::Variance };
+        day.set_option("tree", "overlap").unwrap();
+        assert_eq!(day.part2(), Answer::Int(1));
+        assert!(day.set_option("tree", "fastest").is_err());
+    }
+
+    #[test]
+    fn parses_tick_lists() {
+        assert_eq!(parse_ticks("3"), Ok(vec![3]));
+        assert_eq!(parse_ticks("0..3,10..20:5,7"), Ok(vec![0, 1, 2, 10, 15, 7]));
+        assert!(parse_ticks("1..x").is_err());
+        assert!(parse_ticks("0..10:0").is_err());
+    }
+
+    #[test]
+    fn lays_out_contact_sheets() {
+        let robots = vec![Robot { init_pos: Point::new(0, 0), direction: Vec2::new(1, 0) }];
+        let room = Room::EXAMPLE;
+        let sheet = contact_sheet(&robots, room, &[0, 1, 12], 2, 2);
+        let frame_height = 5 * 2 + SHEET_PADDING + 7 * 2;
+        assert_eq!(sheet.width, 2 * (22 + SHEET_PADDING) + SHEET_PADDING);
+        assert_eq!(sheet.height, 2 * (frame_height + SHEET_PADDING) + SHEET_PADDING);
+
+        // The robot's tile in each frame, below the label.
+        let top = SHEET_PADDING + 5 * 2 + SHEET_PADDING;
+        assert_eq!(sheet.get(SHEET_PADDING, top), Some(ROBOT_COLOUR));
+        assert_eq!(sheet.get(SHEET_PADDING + 2, top), Some(image::BLACK));
+        assert_eq!(sheet.get(2 * SHEET_PADDING + 22 + 2, top), Some(ROBOT_COLOUR));
+        let second_row = top + frame_height + SHEET_PADDING;
+        assert_eq!(sheet.get(SHEET_PADDING + 2, second_row), Some(ROBOT_COLOUR));
+        // Tick labels are drawn in the top strip.
+        assert_eq!(sheet.get(SHEET_PADDING, SHEET_PADDING), Some(image::WHITE));
+    }
+}
@@ -1,244 +1,634 @@
-use crate::file_input;
-use std::io::Write;
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::solver::{Answer, Solver};
 
-#[derive(Copy, Clone)]
-struct point {
-    x: i32,
-    y: i32,
+/// How many moves the puzzle input puts on each line.
+const MOVES_PER_LINE: usize = 1000;
+
+/// Index of a box in `State::blocks`.
+pub type BlockId = usize;
+
+/// A box covering `width` x `height` tiles with its top left at `origin`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub origin: Point,
+    pub width: i32,
+    pub height: i32,
 }
 
-#[derive(Copy, Clone, PartialEq)]
-enum side {
-    left,
-    right,
+impl Block {
+    pub fn tiles(&self) -> impl Iterator<Item = Point> {
+        let (origin, width) = (self.origin, self.width);
+        (0..self.height).flat_map(move |dy| (0..width).map(move |dx| origin + Point::new(dx, dy)))
+    }
 }
 
-struct cell {
-    is_wall: bool,
-    block: Option<side>,
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub is_wall: bool,
+    pub block: Option<BlockId>,
 }
 
-struct state {
-    cells: Vec<Vec<cell>>,
-    robot: point,
-    moves: Vec<point>,
+impl Cell {
+    pub const FLOOR: Cell = Cell { is_wall: false, block: None };
+    pub const WALL: Cell = Cell { is_wall: true, block: None };
 }
 
-fn print_room(cells: &Vec<Vec<cell>>, robot: &point, file_name: &str, append: bool) {
-    let mut file = if append {
-        std::fs::OpenOptions::new().append(true).open(file_name).unwrap()
-    } else {
-        std::fs::File::create(file_name).unwrap()
-    };
+/// Whether boxes keep their size from the map (part 1) or every tile is
+/// doubled in width first (part 2).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Narrow,
+    Wide,
+}
 
-    for i in 0..cells.len() {
-        for j in 0..cells[i].len() {
-            let n = 
-                if i == robot.x as usize && j == robot.y as usize {
-                    "@" 
-                } else if let Some(block) = &cells[i][j].block {
-                    if *block == side::left {
-                        "["
-                    } else {
-                        "]"
-                    }
-                } else if cells[i][j].is_wall {
-                    "#" 
-                } else { 
-                    "." 
-                };
-            write!(file, "{}", n).unwrap();
-        }
-        write!(file, "\n").unwrap();
-    }
-}
-
-fn parse_input(input: &Vec<String>) -> state {
-    let mut ix = 0;
-    let mut cells = Vec::new();
-    let mut robot = point { x: 0, y: 0 };
-    while input[ix].starts_with("#") {
-        let mut row = Vec::new();
-        for jx in 0..input[ix].len() {
-            let c = input[ix].chars().nth(jx).unwrap();
-            //print!("{}", c);
-            if c == '#' {
-                row.push(cell {
-                    is_wall: true,
-                    block: None,
-                });
-                row.push(cell {
-                    is_wall: true,
-                    block: None,
-                });
-            } else if c == 'O' {
-                row.push(cell {
-                    is_wall: false,
-                    block: Some(side::left),
-                });
-                row.push(cell {
-                    is_wall: false,
-                    block: Some(side::right),
-                });
-            } else {
-                row.push(cell {
-                    is_wall: false,
-                    block: None,
-                });
-                if c == '@' {
-                    robot = point { x: ix as i32, y: (jx*2) as i32 };
-                }
-                row.push(cell {
-                    is_wall: false,
-                    block: None,
-                });
-            }
+#[derive(Clone, Debug, PartialEq)]
+pub struct State {
+    pub cells: Grid<Cell>,
+    pub blocks: Vec<Block>,
+    pub robot: Point,
+    pub moves: Vec<Direction>,
+}
+
+impl State {
+    /// Places another box, which must lie on empty floor.
+    pub fn add_block(&mut self, block: Block) -> Result<BlockId, String> {
+        let id = self.blocks.len();
+        if block.width <= 0 || block.height <= 0 {
+            return Err(format!("box at {},{} has no area", block.origin.x, block.origin.y));
         }
-        //print!("\n");
-        cells.push(row);
-        ix += 1;
-    }
-
-    let directions = vec![point { x: 0, y: 1 }, point { x: 0, y: -1 }, point { x: 1, y: 0 }, point { x: -1, y: 0 }];
-    let mut moves = Vec::new();
-    for i in ix..input.len(){
-        for c in input[i].chars(){
-            if c == '>' {
-                moves.push(directions[0]);
-            } else if c == '<' {
-                moves.push(directions[1]);
-            } else if c == 'v' {
-                moves.push(directions[2]);
-            } else if c == '^' {
-                moves.push(directions[3]);
+        for p in block.tiles() {
+            if self.cells.get(p) != Some(&Cell::FLOOR) || p == self.robot {
+                return Err(format!("box at {},{} overlaps {},{}", block.origin.x, block.origin.y, p.x, p.y));
             }
         }
+        for p in block.tiles() {
+            self.cells[p].block = Some(id);
+        }
+        self.blocks.push(block);
+        Ok(id)
     }
+}
 
-    return state { cells: cells, robot: robot, moves: moves };
+/// Draws one-tile boxes as `O` and wider ones as `[`, `-`..., `]`.
+pub fn print_room(cells: &Grid<Cell>, robot: &Point) -> String {
+    let block_at = |p: Point| cells.get(p).and_then(|cell| cell.block);
+    cells.render(|p, cell| {
+        if p == *robot {
+            return '@';
+        }
+        match cell.block {
+            Some(id) => match (block_at(p + Direction::Left) != Some(id), block_at(p + Direction::Right) != Some(id)) {
+                (true, true) => 'O',
+                (true, false) => '[',
+                (false, true) => ']',
+                (false, false) => '-',
+            },
+            None if cell.is_wall => '#',
+            None => '.',
+        }
+    })
 }
 
-fn calculate_next_positions(p: &point, move_: &point, cells: &Vec<Vec<cell>>) -> Vec<point> {
-    let mut points = Vec::new();
-    points.push(point { x: p.x + move_.x, y: p.y + move_.y });
+/// Reads the warehouse map, widened for `mode`, and returns the robot's
+/// position with it. Boxes are `O` or, for wider ones, `[`, any number of
+/// `-`, then `]`.
+pub fn parse_map(lines: &[String], mode: Mode) -> Result<(Grid<Cell>, Vec<Block>, Point), String> {
+    let mut robot = None;
+    let mut blocks = Vec::new();
+    let mut rows = Vec::with_capacity(lines.len());
+    for (y, line) in lines.iter().enumerate() {
+        let line = match mode {
+            Mode::Narrow => line.clone(),
+            Mode::Wide => line
+                .chars()
+                .map(|c| match c {
+                    'O' => "[]".to_string(),
+                    '@' => "@.".to_string(),
+                    c => c.to_string().repeat(2),
+                })
+                .collect(),
+        };
 
-    if move_.y == 0  {
-        if let Some(block) = &cells[p.x as usize][p.y as usize].block {
-            if let Some(block_next) = &cells[(p.x + move_.x) as usize][p.y as usize].block {
-                if block == block_next {
-                    return points;
-                }
+        let mut row = Vec::with_capacity(line.len());
+        let mut open = None;
+        for (x, c) in line.chars().enumerate() {
+            let p = Point::new(x as i32, y as i32);
+            let inside = matches!(c, '-' | ']');
+            if inside != open.is_some() {
+                return Err(format!("unmatched box edge {} at {},{}", c, p.x, p.y));
             }
-            if block == &side::left {
-                points.push(point { x: p.x + move_.x, y: p.y + 1 });
-            } else {
-                points.push(point { x: p.x + move_.x, y: p.y - 1 });
+            match c {
+                '#' => row.push(Cell::WALL),
+                '.' => row.push(Cell::FLOOR),
+                '@' => {
+                    robot = Some(p);
+                    row.push(Cell::FLOOR);
+                }
+                'O' | '[' | '-' | ']' => {
+                    if c != '-' && c != ']' {
+                        open = Some(p);
+                    }
+                    let id = blocks.len();
+                    row.push(Cell { is_wall: false, block: Some(id) });
+                    if c == 'O' || c == ']' {
+                        let origin = open.take().unwrap_or(p);
+                        blocks.push(Block { origin, width: p.x - origin.x + 1, height: 1 });
+                    }
+                }
+                _ => return Err(format!("invalid character in warehouse: {}", c)),
             }
         }
+        if let Some(p) = open {
+            return Err(format!("unclosed box at {},{}", p.x, p.y));
+        }
+        rows.push(row);
     }
-    points
+    let robot = robot.ok_or("no robot in the warehouse")?;
+    Ok((Grid::from_rows(rows)?, blocks, robot))
 }
 
-fn can_move(p: &point, direction: &point, cells: &Vec<Vec<cell>>) -> bool {
-    let next_points = calculate_next_positions(p, direction, cells);
-    for next_point in next_points {
-        if cells[next_point.x as usize][next_point.y as usize].is_wall {
-            return false;
-        }
-        if cells[next_point.x as usize][next_point.y as usize].block.is_some() {
-            if !can_move(&next_point, direction, cells) {
-                return false;
+/// Reads the robot's moves, which may be split over several lines.
+pub fn parse_moves(lines: &[String]) -> Vec<Direction> {
+    lines
+        .iter()
+        .flat_map(|line| line.chars())
+        .filter_map(Direction::from_arrow)
+        .collect()
+}
+
+/// Writes moves back out as the puzzle input does, as lines of arrows.
+pub fn format_moves(moves: &[Direction]) -> String {
+    let arrows = moves.iter().map(|d| d.arrow()).collect::<Vec<char>>();
+    arrows.chunks(MOVES_PER_LINE).map(|line| line.iter().collect::<String>() + "\n").collect()
+}
+
+pub fn parse_input(input: &[String], mode: Mode) -> Result<State, String> {
+    if !input.first().is_some_and(|line| line.starts_with('#')) {
+        return Err("expected the warehouse map first".to_string());
+    }
+    let map_end = input.iter().position(|line| !line.starts_with('#')).unwrap_or(input.len());
+    let (cells, blocks, robot) = parse_map(&input[..map_end], mode)?;
+    Ok(State { cells, blocks, robot, moves: parse_moves(&input[map_end..]) })
+}
+
+/// Everything one push of the robot moves, worked out before any of it is
+/// applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Push {
+    pub direction: Direction,
+    /// Where the robot stood before the push.
+    pub robot: Point,
+    /// The boxes that move, nearest the robot first.
+    pub blocks: Vec<BlockId>,
+}
+
+/// Plans a push: every box reachable through the tiles ahead of the robot,
+/// or `None` if any of them (or the robot) would run into a wall.
+pub fn plan_push(state: &State, direction: Direction) -> Option<Push> {
+    let blocked = |p: Point| state.cells.get(p).is_none_or(|cell| cell.is_wall);
+    let next = state.robot + direction;
+    if blocked(next) {
+        return None;
+    }
+
+    let mut queued = vec![false; state.blocks.len()];
+    let mut blocks = Vec::new();
+    if let Some(id) = state.cells[next].block {
+        queued[id] = true;
+        blocks.push(id);
+    }
+    let mut i = 0;
+    while i < blocks.len() {
+        for p in state.blocks[blocks[i]].tiles() {
+            let ahead = p + direction;
+            if blocked(ahead) {
+                return None;
+            }
+            if let Some(other) = state.cells[ahead].block {
+                if !queued[other] {
+                    queued[other] = true;
+                    blocks.push(other);
+                }
             }
         }
+        i += 1;
     }
-    return true;
+    Some(Push { direction, robot: state.robot, blocks })
 }
 
-fn move_block_horizontally(p: &point, direction: &point, cells: &mut Vec<Vec<cell>>) {
-    let next_positions = calculate_next_positions(p, direction, cells);
-    for next in next_positions {
-        if cells[next.x as usize][next.y as usize].block.is_some() {
-            move_block(&next, direction, cells);
+/// Lifts every box in `blocks` and puts them down again one step along
+/// `direction`. Lifting them all first means the order does not matter.
+fn shift_blocks(state: &mut State, blocks: &[BlockId], direction: Direction) {
+    for id in blocks {
+        for p in state.blocks[*id].tiles() {
+            state.cells[p].block = None;
         }
-        if let Some(block) = &cells[p.x as usize][p.y as usize].block {
-            let side_char = if *block == side::left { "[" } else { "]" };
-            //println!("move block {:?}: {:?},{:?} -> {:?},{:?}", side_char, p.x, p.y, next.x, next.y);
-            cells[next.x as usize][next.y as usize].block = Some(*block);
+    }
+    for id in blocks {
+        state.blocks[*id].origin += direction.delta();
+        for p in state.blocks[*id].tiles() {
+            state.cells[p].block = Some(*id);
         }
     }
 }
 
-fn move_block_vertically(p: &point, direction: &point, cells: &mut Vec<Vec<cell>>, move_other: bool) {
-    let next = calculate_next_positions(p, direction, cells)[0];
-    if cells[next.x as usize][next.y as usize].block.is_some() {
-        //println!("move next block: {:?},{:?}", next.x, next.y);
-        move_block(&next, direction, cells);
-    }
-    if let Some(block) = &cells[p.x as usize][p.y as usize].block {
-        let side_char = if *block == side::left { "[" } else { "]" };
-        let other_side_pos = if *block == side::left { point { x: p.x, y: p.y + 1 } } else { point { x: p.x, y: p.y - 1 } };
-        //println!("move block {:?}: {:?},{:?} -> {:?},{:?}", side_char, p.x, p.y, next.x, next.y);
-        cells[next.x as usize][next.y as usize].block = Some(*block);
-        cells[p.x as usize][p.y as usize].block = None;
+/// Carries out a push from `plan_push` on the state it was planned for.
+pub fn apply(state: &mut State, push: &Push) {
+    shift_blocks(state, &push.blocks, push.direction);
+    state.robot = push.robot + push.direction;
+}
 
-        if move_other {
-            //println!("move block other side: {:?},{:?}", other_side_pos.x, other_side_pos.y);
-            move_block_vertically(&other_side_pos, direction, cells, false);
-        }
-    }
+/// Reverses `push`, which must be the last push applied to `state`.
+pub fn undo(state: &mut State, push: &Push) {
+    shift_blocks(state, &push.blocks, push.direction.opposite());
+    state.robot = push.robot;
+}
+
+/// Tries to move the robot one step, pushing boxes ahead of it. Returns the
+/// push if the robot moved.
+pub fn step(state: &mut State, direction: Direction) -> Option<Push> {
+    let push = plan_push(state, direction)?;
+    apply(state, &push);
+    Some(push)
 }
 
-fn move_block(p: &point, direction: &point, cells: &mut Vec<Vec<cell>>) {
-    if direction.x == 0 {
-        move_block_horizontally(p, direction, cells);
-    } else {
-        move_block_vertically(p, direction, cells, true);
+pub fn move_robot(state: &mut State) {
+    for i in 0..state.moves.len() {
+        step(state, state.moves[i]);
     }
 }
 
-fn move_robot(state: &mut state) {
-    print_room(&state.cells, &state.robot, "room.txt", false);
+/// Walks through a state's moves one at a time, remembering each push so any
+/// of them can be undone. Moves after the current one stay available to redo
+/// until a different move is played.
+#[derive(Clone, Debug)]
+pub struct Stepper {
+    state: State,
+    /// The outcome of each move made so far, `None` where the robot was stuck.
+    pushes: Vec<Option<Push>>,
+    applied: usize,
+}
 
-    for move_ in &state.moves {
-     
-        let next = calculate_next_positions(&state.robot, move_, &state.cells)[0];
-        if state.cells[next.x as usize][next.y as usize].is_wall {
-            continue;
-        }
+impl Stepper {
+    pub fn new(state: State) -> Stepper {
+        Stepper { state, pushes: Vec::new(), applied: 0 }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// How many moves have been applied.
+    pub fn position(&self) -> usize {
+        self.applied
+    }
 
-        if state.cells[next.x as usize][next.y as usize].block.is_some() {
-            if can_move(&next, move_, &mut state.cells) {
-                move_block(&next, move_, &mut state.cells);
-                state.cells[next.x as usize][next.y as usize].block = None;
-                state.robot = next;
+    /// The moves applied so far, in order.
+    pub fn applied_moves(&self) -> &[Direction] {
+        &self.state.moves[..self.applied]
+    }
+
+    /// Applies the next move, or redoes it after an undo. Returns false at
+    /// the end of the moves.
+    pub fn step(&mut self) -> bool {
+        let Some(direction) = self.state.moves.get(self.applied).copied() else {
+            return false;
+        };
+        match self.pushes.get(self.applied) {
+            Some(push) => {
+                if let Some(push) = push.clone() {
+                    apply(&mut self.state, &push);
+                }
             }
-        } else {
-            state.robot = next;
+            None => {
+                let push = step(&mut self.state, direction);
+                self.pushes.push(push);
+            }
+        }
+        self.applied += 1;
+        true
+    }
+
+    /// Takes back the last move. Returns false if there is none.
+    pub fn undo(&mut self) -> bool {
+        if self.applied == 0 {
+            return false;
+        }
+        self.applied -= 1;
+        if let Some(push) = &self.pushes[self.applied] {
+            undo(&mut self.state, push);
         }
+        true
+    }
+
+    /// Steps or undoes until `n` moves are applied, or as close as the
+    /// moves allow.
+    pub fn seek(&mut self, n: usize) {
+        while self.applied > n && self.undo() {}
+        while self.applied < n && self.step() {}
+    }
+
+    /// Makes `direction` the next move in place of any that were left, then
+    /// applies it. Returns the push if the robot moved.
+    pub fn play(&mut self, direction: Direction) -> Option<&Push> {
+        self.state.moves.truncate(self.applied);
+        self.pushes.truncate(self.applied);
+        self.state.moves.push(direction);
+        self.step();
+        self.pushes[self.applied - 1].as_ref()
+    }
 
+    pub fn frame(&self) -> String {
+        print_room(&self.state.cells, &self.state.robot)
+    }
+
+    /// The warehouse after `n` moves.
+    pub fn frame_at(&mut self, n: usize) -> String {
+        self.seek(n);
+        self.frame()
     }
-    print_room(&state.cells, &state.robot, "room.txt", true);
 }
 
-fn calc_answer(cells: &Vec<Vec<cell>>) {
+/// The sum of the boxes' GPS coordinates, measured from each box's top left.
+pub fn calc_answer(cells: &Grid<Cell>) -> usize {
+    let block_at = |p: Point| cells.get(p).and_then(|cell| cell.block);
     let mut sum = 0;
-    for i in 0..cells.len() {
-        for j in 0..cells[i].len() {
-            if let Some(block) = &cells[i as usize][j as usize].block {
-                if *block == side::left {
-                    let y = i * 100;
-                    sum += j + y;
-                }
+    for (p, cell) in cells.iter() {
+        if let Some(id) = cell.block {
+            if block_at(p + Direction::Left) != Some(id) && block_at(p + Direction::Up) != Some(id) {
+                sum += (p.y * 100 + p.x) as usize;
             }
         }
     }
-    println!("sum: {}", sum);
+    sum
 }
 
-pub fn solve(){
-    let mut state = parse_input(&file_input::read_input());
-    
+/// Runs every move and returns the final GPS sum.
+pub fn run_moves(state: &State) -> usize {
+    let mut state = state.clone();
     move_robot(&mut state);
+    calc_answer(&state.cells)
+}
+
+pub struct Day15 {
+    /// `None` when the map already has wide boxes, which have no narrow form.
+    pub narrow: Option<State>,
+    pub wide: State,
+}
+
+impl Solver for Day15 {
+    fn parse(input: &[String]) -> Result<Self, String> {
+        let already_wide = input.iter().take_while(|line| line.starts_with('#')).any(|line| line.contains('['));
+        if already_wide {
+            return Ok(Day15 { narrow: None, wide: parse_input(input, Mode::Narrow)? });
+        }
+        Ok(Day15 { narrow: Some(parse_input(input, Mode::Narrow)?), wide: parse_input(input, Mode::Wide)? })
+    }
+
+    fn part1(&self) -> Answer {
+        self.narrow.as_ref().map_or(Answer::Unsolved, |state| Answer::from(run_moves(state)))
+    }
+
+    fn part2(&self) -> Answer {
+        Answer::from(run_moves(&self.wide))
+    }
+}
+
+/// The smaller warehouse from the puzzle text, shared by the day 15 tests.
+#[cfg(test)]
+pub const SMALL_EXAMPLE: &str = "\
+########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_input::read_lines;
+
+    const LARGE_EXAMPLE: &str = include_str!("../inputs/day15-example.txt");
+
+    const WIDE_EXAMPLE: &str = "\
+#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vv<<^^<<^^
+";
+
+    fn parse(text: &str) -> State {
+        parse_input(&read_lines(text.as_bytes()).unwrap(), Mode::Wide).unwrap()
+    }
+
+    fn parse_narrow(text: &str) -> State {
+        parse_input(&read_lines(text.as_bytes()).unwrap(), Mode::Narrow).unwrap()
+    }
+
+    #[test]
+    fn keeps_narrow_map() {
+        let state = parse_narrow(SMALL_EXAMPLE);
+        assert_eq!((state.cells.width(), state.cells.height()), (8, 8));
+        assert_eq!(state.robot, Point::new(2, 2));
+        assert_eq!(state.moves.len(), 15);
+        assert_eq!(print_room(&state.cells, &state.robot).lines().nth(2), Some("##@.O..#"));
+    }
+
+    #[test]
+    fn pushes_narrow_boxes() {
+        let mut state = parse_narrow(SMALL_EXAMPLE);
+        move_robot(&mut state);
+        assert_eq!(
+            print_room(&state.cells, &state.robot),
+            "########\n#....OO#\n##.....#\n#.....O#\n#.#O@..#\n#...O..#\n#...O..#\n########\n"
+        );
+        assert_eq!(calc_answer(&state.cells), 2028);
+    }
+
+    #[test]
+    fn rejects_unknown_tiles() {
+        let input = read_lines("#####\n#@x.#\n#####\n\n<".as_bytes()).unwrap();
+        assert!(parse_input(&input, Mode::Narrow).is_err());
+        let input = read_lines("####\n#..#\n####\n".as_bytes()).unwrap();
+        assert!(parse_input(&input, Mode::Wide).is_err());
+    }
+
+    #[test]
+    fn widens_the_map() {
+        let state = parse(WIDE_EXAMPLE);
+        assert_eq!((state.cells.width(), state.cells.height()), (14, 7));
+        assert_eq!(state.robot, Point::new(10, 3));
+        assert_eq!(state.moves.len(), 11);
+        assert_eq!(state.blocks.len(), 3);
+        assert_eq!(state.cells[Point::new(6, 3)].block, Some(0));
+        assert_eq!(state.cells[Point::new(7, 3)].block, Some(0));
+        assert_eq!(state.blocks[0], Block { origin: Point::new(6, 3), width: 2, height: 1 });
+        assert_eq!(print_room(&state.cells, &state.robot).lines().nth(3), Some("##....[][]@.##"));
+    }
+
+    #[test]
+    fn pushes_wide_boxes() {
+        let mut state = parse(WIDE_EXAMPLE);
+        move_robot(&mut state);
+        assert_eq!(state.robot, Point::new(5, 2));
+        assert_eq!(
+            print_room(&state.cells, &state.robot),
+            "##############\n##...[].##..##\n##...@.[]...##\n##....[]....##\n\
+             ##..........##\n##..........##\n##############\n"
+        );
+    }
+
+    #[test]
+    fn pushes_boxes_of_mixed_widths() {
+        let input = read_lines("#########\n#.......#\n#.[-]...#\n#.O[]...#\n#..@....#\n#########\n\n^^>".as_bytes())
+            .unwrap();
+        let mut state = parse_input(&input, Mode::Narrow).unwrap();
+        assert_eq!(state.blocks[0], Block { origin: Point::new(2, 2), width: 3, height: 1 });
+        move_robot(&mut state);
+        assert_eq!(
+            print_room(&state.cells, &state.robot),
+            "#########\n#.[-]...#\n#..[]...#\n#.O.@...#\n#.......#\n#########\n"
+        );
+        assert_eq!(step(&mut state, Direction::Up), None);
+        assert!(step(&mut state, Direction::Right).is_some());
+    }
+
+    #[test]
+    fn pushes_rectangular_boxes() {
+        let input = read_lines("#######\n#.....#\n#.....#\n#.....#\n#.....#\n#.@...#\n#######\n".as_bytes()).unwrap();
+        let mut state = parse_input(&input, Mode::Narrow).unwrap();
+        let big = state.add_block(Block { origin: Point::new(2, 3), width: 2, height: 2 }).unwrap();
+        assert!(state.add_block(Block { origin: Point::new(3, 4), width: 1, height: 1 }).is_err());
+        let small = state.add_block(Block { origin: Point::new(3, 2), width: 1, height: 1 }).unwrap();
+
+        assert!(step(&mut state, Direction::Up).is_some());
+        assert_eq!(state.robot, Point::new(2, 4));
+        assert_eq!(state.blocks[big].origin, Point::new(2, 2));
+        assert_eq!(state.blocks[small].origin, Point::new(3, 1));
+        assert_eq!(calc_answer(&state.cells), 202 + 103);
+
+        // The small box is against the wall now, so the big one is stuck too.
+        assert_eq!(step(&mut state, Direction::Up), None);
+        assert_eq!(state.blocks[big].origin, Point::new(2, 2));
+        assert_eq!(print_room(&state.cells, &state.robot).lines().nth(2), Some("#.[]..#"));
+    }
+
+    #[test]
+    fn plans_pushes_before_applying_them() {
+        let input = read_lines("########\n#......#\n#..[]..#\n#.[][].#\n#..[]..#\n#...@..#\n########\n".as_bytes())
+            .unwrap();
+        let mut state = parse_input(&input, Mode::Narrow).unwrap();
+        let before = state.clone();
+
+        let push = plan_push(&state, Direction::Up).unwrap();
+        assert_eq!(push, Push { direction: Direction::Up, robot: Point::new(4, 5), blocks: vec![3, 1, 2, 0] });
+        assert_eq!(state, before);
+
+        apply(&mut state, &push);
+        assert_eq!(
+            print_room(&state.cells, &state.robot),
+            "########\n#..[]..#\n#.[][].#\n#..[]..#\n#...@..#\n#......#\n########\n"
+        );
+        assert_eq!(plan_push(&state, Direction::Up), None);
+
+        undo(&mut state, &push);
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn moves_onto_empty_floor_without_boxes() {
+        let mut state = parse(WIDE_EXAMPLE);
+        let push = step(&mut state, Direction::Right).unwrap();
+        assert!(push.blocks.is_empty());
+        assert_eq!(state.robot, Point::new(11, 3));
+        assert_eq!(step(&mut state, Direction::Right), None);
+    }
+
+    #[test]
+    fn steps_back_and_forth_through_the_moves() {
+        let state = parse(LARGE_EXAMPLE);
+        let mut stepper = Stepper::new(state.clone());
+        let start = stepper.frame();
+
+        let mut fresh = state.clone();
+        for direction in &state.moves[..40] {
+            step(&mut fresh, *direction);
+        }
+        assert_eq!(stepper.frame_at(40), print_room(&fresh.cells, &fresh.robot));
+        assert_eq!(stepper.position(), 40);
+
+        stepper.seek(usize::MAX);
+        assert_eq!(stepper.position(), state.moves.len());
+        assert_eq!(calc_answer(&stepper.state().cells), 9021);
+        assert!(!stepper.step());
+
+        assert_eq!(stepper.frame_at(40), print_room(&fresh.cells, &fresh.robot));
+        stepper.seek(0);
+        assert_eq!(stepper.frame(), start);
+        assert_eq!(stepper.state(), &state);
+        assert!(!stepper.undo());
+    }
+
+    #[test]
+    fn playing_a_move_drops_the_redo_history() {
+        let mut stepper = Stepper::new(parse_narrow(SMALL_EXAMPLE));
+        stepper.seek(3);
+        assert_eq!(stepper.applied_moves(), &[Direction::Left, Direction::Up, Direction::Up]);
+        assert!(stepper.undo());
+
+        let push = stepper.play(Direction::Right).cloned().unwrap();
+        assert_eq!(push.blocks, vec![0]);
+        assert_eq!(stepper.applied_moves(), &[Direction::Left, Direction::Up, Direction::Right]);
+        assert!(!stepper.step());
+        assert_eq!(stepper.state().robot, Point::new(3, 1));
+        assert!(stepper.undo());
+        assert!(stepper.step());
+        assert_eq!(stepper.state().robot, Point::new(3, 1));
+    }
+
+    #[test]
+    fn formats_moves_like_the_input() {
+        let state = parse(LARGE_EXAMPLE);
+        let text = format_moves(&state.moves);
+        assert_eq!(text.lines().count(), 1);
+        assert_eq!(parse_moves(&read_lines(text.as_bytes()).unwrap()), state.moves);
+        assert_eq!(format_moves(&[]), "");
+    }
+
+    #[test]
+    fn rejects_unmatched_box_edges() {
+        for map in ["#[.#", "#.]#", "#[-#"] {
+            assert!(parse_map(&[map.to_string(), "#@.#".to_string()], Mode::Narrow).is_err(), "{}", map);
+        }
+    }
 
-    calc_answer(&state.cells);
-}
\ No newline at end of file
+    #[test]
+    fn solves_large_example() {
+        let day = Day15::parse(&read_lines(LARGE_EXAMPLE.as_bytes()).unwrap()).unwrap();
+        assert_eq!(day.part1(), Answer::Int(10092));
+        assert_eq!(day.part2(), Answer::Int(9021));
+    }
+
+    #[test]
+    fn takes_an_already_wide_map_as_is() {
+        let widened = parse(WIDE_EXAMPLE);
+        let text = format!("{}\n{}", print_room(&widened.cells, &widened.robot), format_moves(&widened.moves));
+        let day = Day15::parse(&read_lines(text.as_bytes()).unwrap()).unwrap();
+        assert_eq!(day.narrow, None);
+        assert_eq!(day.part1(), Answer::Unsolved);
+        assert_eq!(day.wide, widened);
+        assert_eq!(day.part2(), Day15::parse(&read_lines(WIDE_EXAMPLE.as_bytes()).unwrap()).unwrap().part2());
+    }
+}
@@ -1,25 +1,20 @@
-use core::str;
-use std::collections::{HashMap, HashSet, VecDeque, BinaryHeap};
+use std::collections::{HashMap, BinaryHeap};
 use std::cmp::Ordering;
 
-use crate::file_input;
-
-#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy, Ord, PartialOrd)]
-struct Point {
-    x: i32,
-    y: i32,
-}
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::solver::{Answer, Solver};
 
 #[derive(Eq, PartialEq)]
 struct Visit {
-    point: Point,
+    step: Step,
     cost: i32,
 }
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy, Ord, PartialOrd)]
 struct Step {
     point: Point,
-    direction: [i32; 2],
+    direction: Direction,
 }
 
 impl Ord for Visit {
@@ -35,162 +30,149 @@ impl PartialOrd for Visit {
     }
 }
 
-#[derive(Clone)]
-struct Parent {
-    parent: Point,
-    turn: bool,
+pub struct Cell {
+    pub blocked: bool,
 }
 
-struct Cell {
-    blocked: bool,
-    path: bool,
+pub struct Map {
+    pub cells: Grid<Cell>,
+    pub start: Point,
+    pub end: Point,
 }
 
-struct Map {
-    cells: Vec<Vec<Cell>>,
-    start: Point,
-    end: Point,
+pub fn parse_input(input: &[String]) -> Result<Map, String> {
+    let mut start = None;
+    let mut end = None;
+    let cells = Grid::parse(input, |p, c| {
+        if c == '#' {
+            Ok(Cell { blocked: true })
+        } else if c == '.' {
+            Ok(Cell { blocked: false })
+        } else if c == 'S' {
+            start = Some(p);
+            Ok(Cell { blocked: false })
+        } else if c == 'E' {
+            end = Some(p);
+            Ok(Cell { blocked: false })
+        } else {
+            Err(format!("invalid character in input: {}", c))
+        }
+    })?;
+    let start = start.ok_or("no start tile S in the maze")?;
+    let end = end.ok_or("no end tile E in the maze")?;
+    Ok(Map { cells, start, end })
 }
 
-fn parse_input(input: &Vec<String>) -> Map {
-    let mut cells = Vec::new();
-    let mut start = Point { x: 0, y: 0 };
-    let mut end = Point { x: 0, y: 0 };
-    for i in 0..input.len() {
-        let mut row = Vec::new();
-        for j in 0..input[i].len() {
-            let c = input[i].chars().nth(j).unwrap();
-            let cell = 
-                if c == '#' {
-                    Cell { blocked: true, path: false }
-                } else if c == '.' {
-                    Cell { blocked: false, path: false }
-                } else if c == 'S' {
-                    start = Point { x: i as i32, y: j as i32 };
-                    Cell { blocked: false, path: true }
-                } else if c == 'E' {
-                    end = Point { x: i as i32, y: j as i32 };
-                    Cell { blocked: false, path: true }
-                } else {
-                    panic!("Invalid character in input");
-                };
-            row.push(cell);
-        }
-        cells.push(row);
-    }
-    Map { cells, start, end }
+fn is_open(p: Point, map: &Map) -> bool {
+    map.cells.get(p).is_some_and(|cell| !cell.blocked)
 }
 
-fn print_map(map: &Map) {
-    for i in 0..map.cells.len() {
-        for j in 0..map.cells[i].len() {
-            let c = 
-                if map.cells[i][j].blocked {
-                    "#"
-                } else if map.cells[i][j].path {
-                    if map.start.x == i as i32 && map.start.y == j as i32 {
-                        "S"
-                    } else if map.end.x == i as i32 && map.end.y == j as i32 {
-                        "E"
-                    } else {
-                        "o"
-                    }
-                } else {
-                    "."
-                };
-            print!("{}", c);
+// Dijkstra over (tile, facing) pairs: stepping forward costs 1 and turning
+// in place costs 1000. The reindeer starts facing east.
+pub fn bfs_with_path(map: &Map) -> Option<i32> {
+    let mut visited: HashMap<Step, i32> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+
+    let start = Step { point: map.start, direction: Direction::Right };
+    visited.insert(start, 0);
+    queue.push(Visit { step: start, cost: 0 });
+
+    while let Some(visit) = queue.pop() {
+        let Step { point, direction } = visit.step;
+        if point == map.end {
+            return Some(visit.cost);
+        }
+        if visit.cost > visited[&visit.step] {
+            continue;
+        }
+
+        let mut next = vec![
+            (Step { point, direction: direction.turn_left() }, 1000),
+            (Step { point, direction: direction.turn_right() }, 1000),
+        ];
+        let straight = point + direction;
+        if is_open(straight, map) {
+            next.push((Step { point: straight, direction }, 1));
+        }
+
+        for (step, step_cost) in next {
+            let cost = visit.cost + step_cost;
+            if visited.get(&step).is_none_or(|best| cost < *best) {
+                visited.insert(step, cost);
+                queue.push(Visit { step, cost });
+            }
         }
-        print!("\n");
     }
+    None
 }
 
-fn get_neighbors(p: &Point, map: &Map) -> Vec<Point>{
-    let directions = vec![[-1, 0], [1, 0], [0, -1], [0, 1]];
-    let mut neighbors = Vec::new();
-    for d in &directions {
-        let x = p.x + d[0];
-        let y = p.y + d[1];
-        if x >= 0 && y >= 0 && x < map.cells.len() as i32 && y < map.cells[0].len() as i32 && !map.cells[x as usize][y as usize].blocked {
-            neighbors.push(Point { x, y });
-        }
-    };
-    return neighbors;
+pub struct Day16 {
+    pub map: Map,
 }
 
-fn node_point(p: &Point, direction: [i32; 2]) -> Point {
-    Point { x: p.x + direction[0], y: p.y + direction[1] }
+impl Solver for Day16 {
+    fn parse(input: &[String]) -> Result<Self, String> {
+        Ok(Day16 { map: parse_input(input)? })
+    }
+
+    fn part1(&self) -> Answer {
+        bfs_with_path(&self.map).map_or(Answer::Unsolved, Answer::from)
+    }
+
+    fn part2(&self) -> Answer {
+        Answer::Unsolved
+    }
 }
 
-fn bfs_with_path(map: &mut Map) {
-    let mut visited: HashMap<Step, i32> = HashMap::new();
-    let mut queue = BinaryHeap::new();
-    let mut parent_map: HashMap<Point, Option<Parent>> = HashMap::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_input::read_lines;
 
-    queue.push(Visit { point: map.start, cost: 0 });
-    parent_map.insert(map.start, None);
+    const FIRST_EXAMPLE: &str = include_str!("../inputs/day16-example1.txt");
+    const SECOND_EXAMPLE: &str = include_str!("../inputs/day16-example2.txt");
 
-    while let Some(visit) = queue.pop() {
-        //println!("Visiting: {:?} with cost {:?}", visit.point, visit.cost);
-        let node = visit.point;
-        let direction = if let Some(p) = parent_map.get(&node) {
-            if let Some(p) = p {
-                [node.x - p.parent.x, node.y - p.parent.y]
-            } else {
-                [0, 1]
-            }
-        } else {
-            [0, 1]
-        };
+    fn parse(text: &str) -> Day16 {
+        Day16::parse(&read_lines(text.as_bytes()).unwrap()).unwrap()
+    }
 
-        if let Some(parent) = parent_map.get(&visit.point) {
-            if let Some(parent) = parent {
-                visited.insert(Step { point: parent.parent, direction }, visit.cost);
-            }
-        }
+    #[test]
+    fn finds_start_and_end() {
+        let day = parse(FIRST_EXAMPLE);
+        assert_eq!(day.map.start, Point::new(1, 13));
+        assert_eq!(day.map.end, Point::new(13, 1));
+    }
 
-        if !parent_map.contains_key(&node) {
-            parent_map.insert(node, Some(Parent { parent: node, turn: false }));
-        }
+    #[test]
+    fn rejects_unknown_tiles() {
+        let input = vec!["#S.x.E#".to_string()];
+        assert!(parse_input(&input).is_err());
+    }
 
-        let straight = node_point(&node, direction);
-        for neighbor in get_neighbors(&node, map) {
-            if neighbor == map.end {
-                let mut cost = if neighbor == straight { 1 } else { 1001 };
-
-                // Reconstruct the path from start to target
-                map.cells[node.x as usize][node.y as usize].path = true;
-
-                let mut previous = &parent_map[&node];
-                while let Some(n) = previous {
-                    if n.turn {
-                        cost += 1001;
-                    } else {
-                        cost += 1;
-                    }
-                    map.cells[n.parent.x as usize][n.parent.y as usize].path = true;
-                    previous = &parent_map[&n.parent];
-                }
-                println!("Cost: {}", cost);
-                return;
-            }
+    #[test]
+    fn rejects_mazes_without_start_or_end() {
+        let no_end = read_lines("####\n#S##\n####\n".as_bytes()).unwrap();
+        assert_eq!(parse_input(&no_end).err(), Some("no end tile E in the maze".to_string()));
+        let no_start = vec!["#.E#".to_string()];
+        assert!(parse_input(&no_start).is_err());
+    }
 
-            let step_cost = if neighbor == straight { 1 } else { 1001 };
+    #[test]
+    fn solves_first_example() {
+        assert_eq!(parse(FIRST_EXAMPLE).part1(), Answer::Int(7036));
+    }
 
-            let step = Step { point: node, direction };
-            if !visited.contains_key(&step) || visit.cost + step_cost < *visited.get(&step).unwrap() {
-                visited.insert(step, visit.cost + step_cost);
-                queue.push(Visit { point: neighbor, cost: visit.cost + step_cost });
-                parent_map.insert(neighbor, Some(Parent { parent: node, turn: neighbor != straight }));
-            }
-        }
+    #[test]
+    fn solves_second_example() {
+        assert_eq!(parse(SECOND_EXAMPLE).part1(), Answer::Int(11048));
     }
-}
 
-pub fn solve(){
-    let mut map = parse_input(&file_input::read_input());
-    bfs_with_path(&mut map);
-    print_map(&map);
-}
+    #[test]
+    fn turning_costs_a_thousand() {
+        let straight = read_lines("######\n#S..E#\n######\n".as_bytes()).unwrap();
+        assert_eq!(parse_input(&straight).map(|map| bfs_with_path(&map)), Ok(Some(3)));
 
-// 72432 too high
-72428
\ No newline at end of file
+        let corner = read_lines("####\n#.E#\n#S##\n####\n".as_bytes()).unwrap();
+        assert_eq!(parse_input(&corner).map(|map| bfs_with_path(&map)), Ok(Some(2002)));
+    }
+}
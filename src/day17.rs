@@ -1,45 +1,26 @@
-use std::usize;
+use crate::solver::{Answer, Solver};
 
-use crate::file_input;
-
-struct Interpreter {
-    reg_a: i64,
-    reg_b: i64,
-    reg_c: i64,
-    ip: i8,
-    program: Vec<i8>,
-    output: Vec<i64>
+#[derive(Clone)]
+pub struct Interpreter {
+    pub reg_a: i64,
+    pub reg_b: i64,
+    pub reg_c: i64,
+    pub ip: i8,
+    pub program: Vec<i8>,
+    pub output: Vec<i64>
 }
 
 impl Interpreter {
-    fn print(&self) {
-        println!("\na: {}\nb: {}\nc: {}\nip: {}", self.reg_a, self.reg_b, self.reg_c, self.ip);
-        println!("{:?}", self.program);
-        let dash_count = (self.ip as usize) * 3 + 1;
-        print!("{}", "-".repeat(dash_count));
-        println!("^\n");
-        self.print_output();
-    }
-
-    fn print_registers(&self) {
-        println!("a:{:>15} b: {:>15} c: {:>15}", self.reg_a, self.reg_b, self.reg_c);
-    }
-
-    fn print_output(&self) {
-        println!("output: {}", self.output.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(","));
-    }
-
     pub fn execute(&mut self) {
         while self.ip < self.program.len() as i8 {
             //self.print();
-            //self.print_registers();
             let opcode = self.program[self.ip as usize];
             match opcode {
                 0 => {
                     let operand = self.combo_operand();
                     let divisor: i64 = 2_i64.pow(operand as u32);
                     //println!("adv, {:?} / {:?}", self.reg_a, divisor);
-                    self.reg_a = self.reg_a / divisor;
+                    self.reg_a /= divisor;
                 }
                 1 => {
                     let operand = self.literal_operand() as i64;
@@ -85,13 +66,12 @@ impl Interpreter {
              }
                 _ => { panic!("Invalid opcode")}
             }
-            self.ip = self.ip + 2;   
+            self.ip += 2;
         }
     }
 
     fn literal_operand(&self) -> i8 {
-        let operand = self.program[(self.ip + 1) as usize];
-        operand
+        self.program[(self.ip + 1) as usize]
     }
 
     fn combo_operand(&self) -> i64 {
@@ -109,53 +89,129 @@ impl Interpreter {
     }
 }
 
-fn parse_input(input: &Vec<String>) -> Interpreter {
-    let a = input[0].split(":").collect::<Vec<&str>>()[1].trim();
-    let b = input[1].split(":").collect::<Vec<&str>>()[1].trim();
-    let c = input[2].split(":").collect::<Vec<&str>>()[1].trim();
-    let program_str = input[4].split(':').collect::<Vec<&str>>()[1];
-    let program = program_str.split(",").map(|x| x.trim().parse::<i8>().unwrap()).collect::<Vec<i8>>();
-
-    Interpreter { 
-        reg_a: a.parse().unwrap(),
-        reg_b: b.parse().unwrap(),
-        reg_c: c.parse().unwrap(),
-        ip: 2,  
-        program,
-        output: vec![]}
+fn register(line: Option<&String>, name: &str) -> Result<i64, String> {
+    let line = line.ok_or(format!("missing register {}", name))?;
+    let value = line.split(':').nth(1).ok_or(format!("invalid register line: {}", line))?;
+    value.trim().parse().map_err(|_| format!("invalid register value: {}", line))
 }
 
-pub fn solve(){
-    let mut intepreter = parse_input(&file_input::read_input());
-    intepreter.execute();
-    intepreter.print();
+pub fn parse_input(input: &[String]) -> Result<Interpreter, String> {
+    let a = register(input.first(), "A")?;
+    let b = register(input.get(1), "B")?;
+    let c = register(input.get(2), "C")?;
+    let program_line = input.get(4).ok_or("missing program")?;
+    let program_str = program_line.split(':').nth(1).ok_or(format!("invalid program line: {}", program_line))?;
+    let program = program_str
+        .split(',')
+        .map(|x| x.trim().parse::<i8>().map_err(|_| format!("invalid instruction: {}", x)))
+        .collect::<Result<Vec<i8>, String>>()?;
+
+    Ok(Interpreter {
+        reg_a: a,
+        reg_b: b,
+        reg_c: c,
+        ip: 0,
+        program,
+        output: vec![]})
 }
 
-pub fn solvept2(){
-    let answer = vec![2,4,1,5,7,5,4,5,0,3,1,6,5,5,3,0];
-    let mut candidites = vec![0, 1, 2, 3, 4, 5, 6, 7];
+// Each pass through the program prints one digit and shifts A right by three
+// bits, so A can be rebuilt three bits at a time starting from the last output.
+pub fn find_self_output(interpreter: &Interpreter) -> Option<i64> {
+    let answer = interpreter.program.iter().map(|x| *x as i64).collect::<Vec<i64>>();
+    let mut candidates = vec![0];
     let mut new_candidates = vec![];
-    //               109157309200000:
-    for i in answer.iter().rev() {
-        for c in candidites.iter() {
-            let mut intepreter = parse_input(&file_input::read_input());
-            println!("{:?}", c);
-            intepreter.reg_a = *c;
-            intepreter.execute();
-            intepreter.print_output();
-            if intepreter.output[0] == *i {
-                new_candidates.push(*c * 8);
+    for i in (0..answer.len()).rev() {
+        for c in candidates.iter() {
+            for bits in 0..8 {
+                let mut run = interpreter.clone();
+                run.reg_a = *c * 8 + bits;
+                run.execute();
+                if run.output == answer[i..] {
+                    new_candidates.push(*c * 8 + bits);
+                }
             }
         }
-        candidites = new_candidates.clone();
-        println!("{:?}", candidites);
+        candidates = new_candidates.clone();
         new_candidates.clear();
     }
+    candidates.into_iter().min()
+}
+
+pub struct Day17 {
+    pub interpreter: Interpreter,
+}
+
+impl Solver for Day17 {
+    fn parse(input: &[String]) -> Result<Self, String> {
+        Ok(Day17 { interpreter: parse_input(input)? })
+    }
 
-    println!("{:?}", candidites);
+    fn part1(&self) -> Answer {
+        let mut interpreter = self.interpreter.clone();
+        interpreter.execute();
+        Answer::from(interpreter.output)
+    }
+
+    fn part2(&self) -> Answer {
+        find_self_output(&self.interpreter).map_or(Answer::Unsolved, Answer::from)
+    }
 }
 
-//2,4,1,5,7,5,4,5,0,3,1,6,5,5,3,0
-//6,5,1,5,7,5,4,5,0,3,1,6,5,5,3,0
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_input::read_lines;
 
-// 109019930332928 - 109019930329592
+    const EXAMPLE: &str = include_str!("../inputs/day17-example1.txt");
+    const SELF_OUTPUT_EXAMPLE: &str = include_str!("../inputs/day17-example2.txt");
+
+    fn run(a: i64, b: i64, c: i64, program: Vec<i8>) -> Interpreter {
+        let mut interpreter = Interpreter { reg_a: a, reg_b: b, reg_c: c, ip: 0, program, output: vec![] };
+        interpreter.execute();
+        interpreter
+    }
+
+    fn parse(text: &str) -> Day17 {
+        Day17::parse(&read_lines(text.as_bytes()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn bst_reads_combo_operand() {
+        assert_eq!(run(0, 0, 9, vec![2, 6]).reg_b, 1);
+    }
+
+    #[test]
+    fn outputs_literals() {
+        assert_eq!(run(10, 0, 0, vec![5, 0, 5, 1, 5, 4]).output, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn loops_until_a_is_zero() {
+        let interpreter = run(2024, 0, 0, vec![0, 1, 5, 4, 3, 0]);
+        assert_eq!(interpreter.output, vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0]);
+        assert_eq!(interpreter.reg_a, 0);
+    }
+
+    #[test]
+    fn xors_b() {
+        assert_eq!(run(0, 29, 0, vec![1, 7]).reg_b, 26);
+        assert_eq!(run(0, 2024, 43690, vec![4, 0]).reg_b, 44354);
+    }
+
+    #[test]
+    fn solves_example_part1() {
+        assert_eq!(parse(EXAMPLE).part1(), Answer::List(vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]));
+    }
+
+    #[test]
+    fn solves_example_part2() {
+        assert_eq!(parse(SELF_OUTPUT_EXAMPLE).part2(), Answer::Int(117440));
+    }
+
+    #[test]
+    fn rejects_missing_program() {
+        let input = read_lines("Register A: 1\nRegister B: 0\nRegister C: 0\n".as_bytes()).unwrap();
+        assert!(Day17::parse(&input).is_err());
+    }
+}
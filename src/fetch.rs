@@ -0,0 +1,321 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+
+pub const YEAR: u32 = 2024;
+pub const BASE_URL: &str = "https://adventofcode.com";
+const USER_AGENT: &str = "github.com/amunger/AoC2024Rust";
+
+#[derive(Debug)]
+pub enum FetchError {
+    Io(io::Error),
+    Http(String),
+    MissingSession,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::Io(e) => write!(f, "{}", e),
+            FetchError::Http(message) => write!(f, "{}", message),
+            FetchError::MissingSession => write!(f, "no session token; pass --session or set AOC_SESSION"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<io::Error> for FetchError {
+    fn from(e: io::Error) -> Self {
+        FetchError::Io(e)
+    }
+}
+
+/// Fetches the body of `url`, sending `session` as the session cookie.
+pub trait HttpClient {
+    fn get(&self, url: &str, session: Option<&str>) -> Result<String, FetchError>;
+}
+
+/// Shells out to `curl`, which handles HTTPS for the real site.
+pub struct CurlClient;
+
+/// A curl config file setting the session cookie. It is fed to curl on stdin
+/// so the token never shows up in the process list.
+fn curl_config(session: &str) -> String {
+    let quoted = session.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("cookie = \"session={}\"\n", quoted)
+}
+
+impl HttpClient for CurlClient {
+    fn get(&self, url: &str, session: Option<&str>) -> Result<String, FetchError> {
+        let session = session.ok_or(FetchError::MissingSession)?;
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--user-agent", USER_AGENT])
+            .args(["--config", "-"])
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child.stdin.take().expect("curl stdin is piped");
+        stdin.write_all(curl_config(session).as_bytes())?;
+        drop(stdin);
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(FetchError::Http(format!("curl {}: {}", url, String::from_utf8_lossy(&output.stderr).trim())));
+        }
+        String::from_utf8(output.stdout).map_err(|_| FetchError::Http(format!("{} did not return text", url)))
+    }
+}
+
+/// Minimal HTTP/1.1 client over a plain socket, for `http://` URLs such as a
+/// local mock server.
+pub struct PlainHttpClient;
+
+impl HttpClient for PlainHttpClient {
+    fn get(&self, url: &str, session: Option<&str>) -> Result<String, FetchError> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or(FetchError::Http(format!("not an http:// url: {}", url)))?;
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+
+        let mut stream = TcpStream::connect(host)?;
+        let mut request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nConnection: close\r\n", path, host, USER_AGENT);
+        if let Some(session) = session {
+            request.push_str(&format!("Cookie: session={}\r\n", session));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes())?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        let malformed = || FetchError::Http(format!("{}: malformed response", url));
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").ok_or_else(malformed)?;
+        let head = String::from_utf8_lossy(&response[..split]);
+        let body = &response[split + 4..];
+        let mut lines = head.lines();
+        let status = lines.next().unwrap_or("");
+        if status.split_whitespace().nth(1) != Some("200") {
+            return Err(FetchError::Http(format!("{}: {}", url, status)));
+        }
+
+        let headers: Vec<(String, &str)> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim()))
+            .collect();
+        let header = |name: &str| headers.iter().find(|(n, _)| n == name).map(|(_, v)| *v);
+
+        let body = if header("transfer-encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked")) {
+            decode_chunked(body).ok_or_else(malformed)?
+        } else if let Some(length) = header("content-length") {
+            let length: usize = length.parse().map_err(|_| malformed())?;
+            if body.len() < length {
+                return Err(FetchError::Http(format!("{}: body cut short at {} of {} bytes", url, body.len(), length)));
+            }
+            body[..length].to_vec()
+        } else {
+            body.to_vec()
+        };
+        String::from_utf8(body).map_err(|_| FetchError::Http(format!("{} did not return text", url)))
+    }
+}
+
+/// Joins the chunks of a `Transfer-Encoding: chunked` body, or `None` if the
+/// body is not validly chunked or stops before the last chunk.
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size_line = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size_hex = size_line.split(';').next()?.trim();
+        let size = usize::from_str_radix(size_hex, 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(decoded);
+        }
+        if body.len() < size + 2 || &body[size..size + 2] != b"\r\n" {
+            return None;
+        }
+        decoded.extend_from_slice(&body[..size]);
+        body = &body[size + 2..];
+    }
+}
+
+/// Serves inputs from a directory laid out like the site's URL paths, e.g.
+/// `<dir>/2024/day/16/input`, so fetching works with no network at all.
+pub struct FixtureClient {
+    pub dir: PathBuf,
+}
+
+impl HttpClient for FixtureClient {
+    fn get(&self, url: &str, _session: Option<&str>) -> Result<String, FetchError> {
+        let path = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let path = path.find('/').map_or("", |i| &path[i + 1..]);
+        let file = self.dir.join(path);
+        fs::read_to_string(&file).map_err(|e| FetchError::Http(format!("no fixture at {}: {}", file.display(), e)))
+    }
+}
+
+/// Picks the client for `base_url`: plain sockets for `http://`, curl otherwise.
+pub fn client_for(base_url: &str) -> Box<dyn HttpClient> {
+    if base_url.starts_with("http://") {
+        Box::new(PlainHttpClient)
+    } else {
+        Box::new(CurlClient)
+    }
+}
+
+pub fn input_url(base_url: &str, day: u8) -> String {
+    format!("{}/{}/day/{}/input", base_url.trim_end_matches('/'), YEAR, day)
+}
+
+/// Returns the cached input for `day` under `cache_dir`, downloading it first
+/// if it is not there yet. A cached input is never fetched again.
+pub fn fetch_input(
+    day: u8,
+    client: &dyn HttpClient,
+    base_url: &str,
+    session: Option<&str>,
+    cache_dir: &Path,
+) -> Result<PathBuf, FetchError> {
+    let path = cache_dir.join(format!("day{:02}.txt", day));
+    if path.exists() {
+        return Ok(path);
+    }
+
+    let body = client.get(&input_url(base_url, day), session)?;
+    fs::create_dir_all(cache_dir)?;
+    // Write next to the real file and rename it into place, so an interrupted
+    // write never leaves a partial input that later looks cached.
+    let temp = cache_dir.join(format!(".day{:02}.txt.{}.tmp", day, process::id()));
+    if let Err(e) = fs::write(&temp, body).and_then(|_| fs::rename(&temp, &path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc-fetch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    struct Unreachable;
+
+    impl HttpClient for Unreachable {
+        fn get(&self, url: &str, _session: Option<&str>) -> Result<String, FetchError> {
+            panic!("unexpected fetch of {}", url);
+        }
+    }
+
+    #[test]
+    fn builds_input_urls() {
+        assert_eq!(input_url("https://adventofcode.com/", 7), "https://adventofcode.com/2024/day/7/input");
+    }
+
+    #[test]
+    fn reads_fixture_directories() {
+        let fixtures = temp_dir("fixtures");
+        fs::create_dir_all(fixtures.join("2024/day/14")).unwrap();
+        fs::write(fixtures.join("2024/day/14/input"), "p=0,4 v=3,-3\n").unwrap();
+        let cache = temp_dir("fixture-cache");
+
+        let client = FixtureClient { dir: fixtures };
+        let path = fetch_input(14, &client, BASE_URL, None, &cache).unwrap();
+        assert_eq!(path, cache.join("day14.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "p=0,4 v=3,-3\n");
+
+        // Once cached, the input is never requested again.
+        assert_eq!(fetch_input(14, &Unreachable, BASE_URL, None, &cache).unwrap(), path);
+    }
+
+    /// Answers one request on a local port with `response`, and hands back
+    /// the request's lines once it has been served.
+    fn serve_once(response: &'static [u8]) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+                request.push(line.trim_end().to_string());
+            }
+            stream.write_all(response).unwrap();
+            request
+        });
+        (base_url, server)
+    }
+
+    #[test]
+    fn fetches_from_a_local_server() {
+        let (base_url, server) = serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\nA: 729\n\n");
+
+        let cache = temp_dir("server-cache");
+        let path = fetch_input(17, &PlainHttpClient, &base_url, Some("abc"), &cache).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "A: 729\n\n");
+        assert_eq!(fs::read_dir(&cache).unwrap().count(), 1);
+
+        let request = server.join().unwrap();
+        assert_eq!(request[0], "GET /2024/day/17/input HTTP/1.1");
+        assert!(request.contains(&"Cookie: session=abc".to_string()));
+    }
+
+    #[test]
+    fn decodes_chunked_bodies() {
+        let (base_url, server) =
+            serve_once(b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n4\r\nA: 7\r\n4;x=y\r\n29\n\n\r\n0\r\n\r\n");
+        assert_eq!(PlainHttpClient.get(&input_url(&base_url, 17), None).unwrap(), "A: 729\n\n");
+        server.join().unwrap();
+
+        assert_eq!(decode_chunked(b"3\r\nabc\r\n"), None);
+        assert_eq!(decode_chunked(b"5\r\nabc\r\n0\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn rejects_short_bodies() {
+        let (base_url, server) = serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 20\r\n\r\nA: 729\n");
+        let cache = temp_dir("short-cache");
+        let result = fetch_input(17, &PlainHttpClient, &base_url, None, &cache);
+        server.join().unwrap();
+        assert!(matches!(result, Err(FetchError::Http(message)) if message.contains("cut short")));
+        assert!(!cache.join("day17.txt").exists());
+    }
+
+    #[test]
+    fn quotes_the_session_for_curl() {
+        assert_eq!(curl_config("abc"), "cookie = \"session=abc\"\n");
+        assert_eq!(curl_config(r#"a"b\c"#), "cookie = \"session=a\\\"b\\\\c\"\n");
+    }
+
+    #[test]
+    fn reports_http_errors() {
+        let (base_url, server) = serve_once(b"HTTP/1.1 404 Not Found\r\n\r\n");
+
+        let cache = temp_dir("error-cache");
+        let result = fetch_input(3, &PlainHttpClient, &base_url, None, &cache);
+        server.join().unwrap();
+        assert!(matches!(result, Err(FetchError::Http(_))));
+        assert!(!cache.join("day03.txt").exists());
+    }
+}
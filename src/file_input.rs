@@ -1,11 +1,64 @@
-use std::{fs::File, io::{BufRead, BufReader}};
+use std::{fmt, fs::File, io::{self, BufRead, BufReader}, path::PathBuf};
 
-pub fn read_input() -> Vec<String> {
-    let mut input = vec![];
-    let file = File::open("input.txt").expect("File not found");
-    let reader = BufReader::new(file);
-    for line in reader.lines() {
-        input.push(line.unwrap());
+/// Where a puzzle's input lines come from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+    Path(PathBuf),
+    Stdin,
+    /// `inputs/dayNN.txt` for the given day.
+    Default(u8),
+}
+
+impl InputSource {
+    /// Interprets a command-line value: `-` is stdin, anything else a path.
+    pub fn from_arg(arg: &str) -> InputSource {
+        if arg == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::Path(PathBuf::from(arg))
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            InputSource::Path(path) => path.display().to_string(),
+            InputSource::Stdin => "stdin".to_string(),
+            InputSource::Default(day) => default_path(*day).display().to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InputError {
+    pub source_name: String,
+    pub error: io::Error,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "could not read input from {}: {}", self.source_name, self.error)
     }
-    input
-}
\ No newline at end of file
+}
+
+impl std::error::Error for InputError {}
+
+pub fn default_path(day: u8) -> PathBuf {
+    PathBuf::from(format!("inputs/day{:02}.txt", day))
+}
+
+pub fn read_input(source: &InputSource) -> Result<Vec<String>, InputError> {
+    let error = |error| InputError { source_name: source.name(), error };
+    match source {
+        InputSource::Stdin => read_lines(io::stdin().lock()).map_err(error),
+        InputSource::Path(path) => read_file(path).map_err(error),
+        InputSource::Default(day) => read_file(&default_path(*day)).map_err(error),
+    }
+}
+
+fn read_file(path: &PathBuf) -> io::Result<Vec<String>> {
+    read_lines(BufReader::new(File::open(path)?))
+}
+
+pub fn read_lines<R: BufRead>(reader: R) -> io::Result<Vec<String>> {
+    reader.lines().collect()
+}
@@ -0,0 +1,207 @@
+use std::ops::{Index, IndexMut};
+
+use crate::geometry::{Direction, Point};
+
+/// A rectangular grid stored row by row. Positions are `Point`s with `x` the
+/// column and `y` the row, starting from the top left.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+const DIAGONALS: [Point; 4] = [Point::new(-1, -1), Point::new(1, -1), Point::new(1, 1), Point::new(-1, 1)];
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, String> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(format!("row {} has {} cells, expected {}", y, row.len(), width));
+            }
+            cells.extend(row);
+        }
+        Ok(Grid { width, height, cells })
+    }
+
+    /// Builds a grid from a character map, one cell per character. `f` gets
+    /// the position of each character so callers can note special tiles.
+    pub fn parse<F>(lines: &[String], mut f: F) -> Result<Grid<T>, String>
+    where
+        F: FnMut(Point, char) -> Result<T, String>,
+    {
+        let mut rows = Vec::with_capacity(lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(x, c)| f(Point::new(x as i32, y as i32), c))
+                .collect::<Result<Vec<T>, String>>()?;
+            rows.push(row);
+        }
+        Grid::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, p: Point) -> bool {
+        p.x >= 0 && p.y >= 0 && (p.x as usize) < self.width && (p.y as usize) < self.height
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        if self.in_bounds(p) {
+            Some(&self.cells[p.y as usize * self.width + p.x as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        if self.in_bounds(p) {
+            Some(&mut self.cells[p.y as usize * self.width + p.x as usize])
+        } else {
+            None
+        }
+    }
+
+    /// The in-bounds orthogonal neighbours of `p`.
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL
+            .into_iter()
+            .map(move |d| p + d)
+            .filter(|n| self.in_bounds(*n))
+    }
+
+    /// The in-bounds orthogonal and diagonal neighbours of `p`.
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL
+            .into_iter()
+            .map(|d| d.delta())
+            .chain(DIAGONALS)
+            .map(move |d| p + d)
+            .filter(|n| self.in_bounds(*n))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1)).take(self.height)
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| (Point::new((i % width) as i32, (i / width) as i32), cell))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// Renders the grid back to text, one line per row.
+    pub fn render<F>(&self, mut f: F) -> String
+    where
+        F: FnMut(Point, &T) -> char,
+    {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for (p, cell) in self.iter() {
+            text.push(f(p, cell));
+            if p.x as usize == self.width - 1 {
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p).unwrap_or_else(|| panic!("({}, {}) is outside the grid", p.x, p.y))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        self.get_mut(p).unwrap_or_else(|| panic!("({}, {}) is outside the grid", p.x, p.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        let lines = vec!["#.#".to_string(), "..#".to_string()];
+        Grid::parse(&lines, |_, c| Ok(c)).unwrap()
+    }
+
+    #[test]
+    fn parses_character_maps() {
+        let grid = sample();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(Point::new(2, 1)), Some(&'#'));
+        assert_eq!(grid[Point::new(1, 0)], '.');
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(0, -1)), None);
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        let lines = vec!["##".to_string(), "#".to_string()];
+        assert!(Grid::parse(&lines, |_, c| Ok(c)).is_err());
+    }
+
+    #[test]
+    fn parse_errors_propagate() {
+        let lines = vec!["#x".to_string()];
+        let result: Result<Grid<char>, String> =
+            Grid::parse(&lines, |p, c| if c == '#' { Ok(c) } else { Err(format!("bad {} at {},{}", c, p.x, p.y)) });
+        assert_eq!(result, Err("bad x at 1,0".to_string()));
+    }
+
+    #[test]
+    fn iterates_neighbours_inside_bounds() {
+        let grid = sample();
+        let mut four = grid.neighbours4(Point::new(0, 0)).collect::<Vec<_>>();
+        four.sort();
+        assert_eq!(four, vec![Point::new(0, 1), Point::new(1, 0)]);
+        assert_eq!(grid.neighbours8(Point::new(1, 0)).count(), 5);
+    }
+
+    #[test]
+    fn iterates_rows_and_columns() {
+        let grid = sample();
+        assert_eq!(grid.rows().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>(), vec!["#.#", "..#"]);
+        assert_eq!(grid.column(2).collect::<String>(), "##");
+    }
+
+    #[test]
+    fn renders_back_to_text() {
+        let mut grid = sample();
+        grid[Point::new(0, 1)] = '@';
+        assert_eq!(grid.render(|_, c| *c), "#.#\n@.#\n");
+    }
+}
@@ -0,0 +1,235 @@
+use crate::grid::Grid;
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// 3x5 bitmaps for the digits, one row per entry with the high bit on the left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+pub const GLYPH_WIDTH: usize = 3;
+pub const GLYPH_HEIGHT: usize = 5;
+
+/// An RGB image stored row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Image {
+        Image { width, height, pixels: vec![fill; width * height] }
+    }
+
+    /// Draws each grid cell as a `scale` x `scale` block coloured by `colour`.
+    pub fn from_grid<T, F>(grid: &Grid<T>, scale: usize, mut colour: F) -> Image
+    where
+        F: FnMut(&T) -> Rgb,
+    {
+        let mut image = Image::new(grid.width() * scale, grid.height() * scale, BLACK);
+        for (p, cell) in grid.iter() {
+            image.fill_rect(p.x as usize * scale, p.y as usize * scale, scale, scale, colour(cell));
+        }
+        image
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Sets a pixel; anything outside the image is clipped.
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = colour;
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, colour: Rgb) {
+        for dy in 0..height {
+            for dx in 0..width {
+                self.set(x + dx, y + dy, colour);
+            }
+        }
+    }
+
+    /// Copies `other` onto this image with its top left corner at `(x, y)`.
+    pub fn blit(&mut self, other: &Image, x: usize, y: usize) {
+        for oy in 0..other.height {
+            for ox in 0..other.width {
+                self.set(x + ox, y + oy, other.pixels[oy * other.width + ox]);
+            }
+        }
+    }
+
+    /// Writes the digits of `text` with the built-in font, each font pixel
+    /// `scale` pixels wide. Other characters are left as blank space.
+    pub fn draw_text(&mut self, text: &str, x: usize, y: usize, scale: usize, colour: Rgb) {
+        for (i, c) in text.chars().enumerate() {
+            let Some(glyph) = c.to_digit(10).map(|d| DIGITS[d as usize]) else {
+                continue;
+            };
+            let left = x + i * (GLYPH_WIDTH + 1) * scale;
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                        self.fill_rect(left + col * scale, y + row * scale, scale, scale, colour);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Binary PPM (P6).
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend(self.pixels.iter().flatten());
+        bytes
+    }
+
+    /// An 8-bit RGB PNG. The image data is stored uncompressed inside the
+    /// zlib stream, which every decoder accepts and needs no deflate encoder.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// A zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn writes_ppm() {
+        let mut image = Image::new(2, 1, BLACK);
+        image.set(1, 0, [1, 2, 3]);
+        assert_eq!(image.to_ppm(), b"P6\n2 1\n255\n\x00\x00\x00\x01\x02\x03".to_vec());
+    }
+
+    #[test]
+    fn writes_png_chunks() {
+        let png = Image::new(3, 2, WHITE).to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(&png[png.len() - 12..], b"\x00\x00\x00\x00IEND\xae\x42\x60\x82");
+
+        // Two rows of a filter byte and three white pixels, stored as is.
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let zlib = &png[41..41 + idat_len];
+        assert_eq!(&zlib[..7], &[0x78, 0x01, 1, 20, 0, !20, 0xff]);
+        assert_eq!(zlib[7], 0);
+        assert_eq!(&zlib[8..17], &[255; 9]);
+    }
+
+    #[test]
+    fn splits_large_images_into_blocks() {
+        let data = vec![7; 0x1_0000 + 5];
+        let zlib = zlib_stored(&data);
+        assert_eq!(zlib.len(), 2 + 5 + 0xffff + 5 + 6 + 4);
+        assert_eq!(zlib[2], 0);
+        assert_eq!(zlib[2 + 5 + 0xffff], 1);
+    }
+
+    #[test]
+    fn scales_grids_and_draws_digits() {
+        let grid = Grid::from_rows(vec![vec![false, true]]).unwrap();
+        let image = Image::from_grid(&grid, 2, |on| if *on { WHITE } else { BLACK });
+        assert_eq!((image.width, image.height), (4, 2));
+        assert_eq!(image.get(3, 1), Some(WHITE));
+        assert_eq!(image.get(1, 1), Some(BLACK));
+        assert_eq!(image.get(4, 0), None);
+
+        let mut text = Image::new(7, 5, BLACK);
+        text.draw_text("17", 0, 0, 1, WHITE);
+        let lit = Grid::from_rows((0..5).map(|y| (0..7).map(|x| text.get(x, y) == Some(WHITE)).collect()).collect())
+            .unwrap();
+        assert_eq!(
+            lit.render(|_, on| if *on { '#' } else { '.' }),
+            ".#..###\n##....#\n.#...#.\n.#...#.\n###..#.\n"
+        );
+        assert!(lit[Point::new(1, 0)]);
+    }
+}
@@ -0,0 +1,18 @@
+pub mod bench;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod fetch;
+pub mod file_input;
+pub mod geometry;
+pub mod grid;
+pub mod image;
+pub mod math;
+pub mod play;
+pub mod solutions;
+pub mod solver;
+pub mod terminal;
+pub mod torus;
+pub mod verify;
+pub mod viewer;
@@ -1,7 +1,401 @@
-mod day17;
-mod file_input;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+
+use aoc2024::day14::{self, Day14};
+use aoc2024::day15::{self, Mode, Stepper};
+use aoc2024::file_input::{self, InputSource};
+use aoc2024::solutions::{self, Solution, SOLUTIONS};
+use aoc2024::fetch::{self, FixtureClient, HttpClient};
+use aoc2024::solver::Solver;
+use aoc2024::{bench, play, verify, viewer};
+
+const USAGE: &str = "usage:
+    aoc list
+    aoc run --day <N> [--part <1|2>] [--input <path|->] [--opt <name=value>]...
+    aoc run --all [--part <1|2>]
+    aoc verify [--answers <path>]
+    aoc bench [--day <N>] [--runs <R>] [--input <path|->]
+    aoc fetch --day <N> [--session <token>] [--base-url <url>] [--fixtures <dir>]
+    aoc view [--input <path>] [--opt room=<W>x<H>] [--tick <N>]
+    aoc export --ticks <list> --out <file.png|file.ppm> [--input <path|->] [--opt room=<W>x<H>]
+               [--columns <N>] [--scale <S>]
+    aoc stats [--input <path|->] [--opt room=<W>x<H>] [--ticks <list>] [--out <file.csv>]
+    aoc replay --at <N>... [--input <path|->] [--wide]
+    aoc play [--input <path>] [--wide] [--record <file>]
+
+Inputs default to inputs/dayNN.txt; `-` reads from stdin.
+Day options: 14 takes room=<W>x<H>, ticks=<N> and tree=<variance|overlap>.
+view plays back the day 14 robots in the terminal; export draws them as an image,
+one frame per tick in a contact sheet; stats writes per-tick CSV for a whole period
+by default. Ticks are a list like 0..100,7153 or 0..10403:101.
+replay prints the day 15 warehouse after each given number of moves; play lets you
+move the robot yourself, and --record saves the map and your moves as a puzzle input.
+fetch caches into inputs/ and reads the session token from AOC_SESSION by default.";
+
+struct RunArgs {
+    day: Option<u8>,
+    part: Option<u8>,
+    input: Option<InputSource>,
+    all: bool,
+    options: Vec<(String, String)>,
+}
+
+fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+    let mut run_args = RunArgs { day: None, part: None, input: None, all: false, options: Vec::new() };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--day" | "-d" => run_args.day = Some(parse_number(iter.next(), "--day")?),
+            "--part" | "-p" => run_args.part = Some(parse_number(iter.next(), "--part")?),
+            "--input" | "-i" => {
+                let path = iter.next().ok_or("--input needs a value")?;
+                run_args.input = Some(InputSource::from_arg(path));
+            }
+            "--all" => run_args.all = true,
+            "--opt" | "-o" => {
+                let option = iter.next().ok_or("--opt needs a value")?;
+                let (name, value) = option.split_once('=').ok_or(format!("--opt expects name=value, got {}", option))?;
+                run_args.options.push((name.to_string(), value.to_string()));
+            }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    if run_args.all == run_args.day.is_some() {
+        return Err("pass either --day <N> or --all".to_string());
+    }
+    // Every day has its own input format and options.
+    if run_args.all && run_args.input.is_some() {
+        return Err("--input needs --day".to_string());
+    }
+    if run_args.all && !run_args.options.is_empty() {
+        return Err("--opt needs --day".to_string());
+    }
+    if run_args.part.is_some_and(|part| part != 1 && part != 2) {
+        return Err("--part expects 1 or 2".to_string());
+    }
+    Ok(run_args)
+}
+
+fn parse_number<T: FromStr>(value: Option<&String>, flag: &str) -> Result<T, String> {
+    let value = value.ok_or(format!("{} needs a value", flag))?;
+    value.parse().map_err(|_| format!("{} expects a number, got {}", flag, value))
+}
+
+fn list() {
+    for solution in SOLUTIONS {
+        for part in [1, 2] {
+            let status = if solution.parts.contains(&part) { "" } else { "  unsolved" };
+            println!("day {:>2} part {}{}", solution.day, part, status);
+        }
+    }
+}
+
+fn run_solution(solution: &Solution, args: &RunArgs) -> Result<(), String> {
+    let source = args.input.clone().unwrap_or(InputSource::Default(solution.day));
+    let input = file_input::read_input(&source).map_err(|e| e.to_string())?;
+    let mut puzzle = (solution.parse)(&input)?;
+    for (name, value) in &args.options {
+        puzzle.set_option(name, value)?;
+    }
+    let parts = match args.part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+    for part in parts {
+        println!("day {:>2} part {}: {}", solution.day, part, puzzle.solve(part));
+    }
+    Ok(())
+}
+
+fn run(args: &RunArgs) -> Result<(), String> {
+    let selected: Vec<&Solution> = SOLUTIONS
+        .iter()
+        .filter(|s| args.all || Some(s.day) == args.day)
+        .collect();
+    if selected.is_empty() {
+        return Err("no matching solution".to_string());
+    }
+
+    let mut failed = 0;
+    for solution in selected {
+        if let Err(e) = run_solution(solution, args) {
+            eprintln!("day {:>2}: {}", solution.day, e);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} day(s) failed", failed));
+    }
+    Ok(())
+}
+
+struct BenchArgs {
+    day: Option<u8>,
+    runs: usize,
+    input: Option<InputSource>,
+}
+
+fn parse_bench_args(args: &[String]) -> Result<BenchArgs, String> {
+    let mut bench_args = BenchArgs { day: None, runs: 10, input: None };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--day" | "-d" => bench_args.day = Some(parse_number(iter.next(), "--day")?),
+            "--runs" | "-r" => bench_args.runs = parse_number(iter.next(), "--runs")?,
+            "--input" | "-i" => {
+                let path = iter.next().ok_or("--input needs a value")?;
+                bench_args.input = Some(InputSource::from_arg(path));
+            }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    if bench_args.input.is_some() && bench_args.day.is_none() {
+        return Err("--input needs --day".to_string());
+    }
+    Ok(bench_args)
+}
+
+fn bench(args: &BenchArgs) -> Result<(), String> {
+    if let Some(day) = args.day {
+        let solution = solutions::find(day).ok_or(format!("no solution for day {}", day))?;
+        let source = args.input.clone().unwrap_or(InputSource::Default(day));
+        let input = file_input::read_input(&source).map_err(|e| e.to_string())?;
+        bench::print_day(&bench::bench_day(solution, &input, args.runs)?);
+        return Ok(());
+    }
+
+    let mut all = Vec::new();
+    for solution in SOLUTIONS {
+        let timings = file_input::read_input(&InputSource::Default(solution.day))
+            .map_err(|e| e.to_string())
+            .and_then(|input| bench::bench_day(solution, &input, args.runs));
+        match timings {
+            Ok(timings) => all.push(timings),
+            Err(e) => eprintln!("day {:>2}: {}", solution.day, e),
+        }
+    }
+    bench::print_year(&all);
+    Ok(())
+}
+
+fn fetch(args: &[String]) -> Result<(), String> {
+    let mut day = None;
+    let mut session = env::var("AOC_SESSION").ok();
+    let mut base_url = fetch::BASE_URL.to_string();
+    let mut fixtures = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--day" | "-d" => day = Some(parse_number(iter.next(), "--day")?),
+            "--session" => session = Some(iter.next().ok_or("--session needs a value")?.clone()),
+            "--base-url" => base_url = iter.next().ok_or("--base-url needs a value")?.clone(),
+            "--fixtures" => fixtures = Some(PathBuf::from(iter.next().ok_or("--fixtures needs a value")?)),
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    let day = day.ok_or("fetch needs --day <N>")?;
+
+    let client: Box<dyn HttpClient> = match fixtures {
+        Some(dir) => Box::new(FixtureClient { dir }),
+        None => fetch::client_for(&base_url),
+    };
+    let path = fetch::fetch_input(day, client.as_ref(), &base_url, session.as_deref(), Path::new("inputs"))
+        .map_err(|e| format!("day {}: {}", day, e))?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+fn view(args: &[String]) -> Result<(), String> {
+    let mut source = InputSource::Default(14);
+    let mut options = Vec::new();
+    let mut tick = 0;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" | "-i" => source = InputSource::from_arg(iter.next().ok_or("--input needs a value")?),
+            "--opt" | "-o" => options.push(iter.next().ok_or("--opt needs a value")?.clone()),
+            "--tick" | "-t" => tick = parse_number(iter.next(), "--tick")?,
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    if source == InputSource::Stdin {
+        return Err("view reads keys from stdin; pass the input as a file".to_string());
+    }
+
+    let day = load_day14(&source, &options)?;
+    viewer::run(&day.robots, day.room, tick).map_err(|e| format!("view: {}", e))
+}
+
+/// Reads the day 14 input and applies `--opt` values, as `run --day 14` would.
+fn load_day14(source: &InputSource, options: &[String]) -> Result<Day14, String> {
+    let input = file_input::read_input(source).map_err(|e| e.to_string())?;
+    let mut day = Day14::parse(&input)?;
+    for option in options {
+        let (name, value) = option.split_once('=').ok_or(format!("--opt expects name=value, got {}", option))?;
+        day.set_option(name, value)?;
+    }
+    Ok(day)
+}
+
+fn export(args: &[String]) -> Result<(), String> {
+    let mut source = InputSource::Default(14);
+    let mut options = Vec::new();
+    let mut ticks = None;
+    let mut out = None;
+    let mut columns = 10;
+    let mut scale = 2;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" | "-i" => source = InputSource::from_arg(iter.next().ok_or("--input needs a value")?),
+            "--opt" | "-o" => options.push(iter.next().ok_or("--opt needs a value")?.clone()),
+            "--ticks" | "-t" => ticks = Some(day14::parse_ticks(iter.next().ok_or("--ticks needs a value")?)?),
+            "--out" => out = Some(PathBuf::from(iter.next().ok_or("--out needs a value")?)),
+            "--columns" => columns = parse_number(iter.next(), "--columns")?,
+            "--scale" => scale = parse_number(iter.next(), "--scale")?,
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    let ticks = ticks.ok_or("export needs --ticks <list>")?;
+    let out = out.ok_or("export needs --out <file>")?;
+    if ticks.is_empty() || scale == 0 {
+        return Err("export needs at least one tick and a scale of 1 or more".to_string());
+    }
+
+    let day = load_day14(&source, &options)?;
+    let image = match ticks.as_slice() {
+        [tick] => {
+            let tick = tick.rem_euclid(day.room.period()) as i32;
+            day14::room_image(&day14::room_after_times(&day.robots, tick, day.room), scale)
+        }
+        _ => day14::contact_sheet(&day.robots, day.room, &ticks, columns, scale),
+    };
+    let bytes = match out.extension().and_then(|e| e.to_str()) {
+        Some("png") => image.to_png(),
+        Some("ppm") => image.to_ppm(),
+        _ => return Err(format!("{}: expected a .png or .ppm file", out.display())),
+    };
+    fs::write(&out, bytes).map_err(|e| format!("could not write {}: {}", out.display(), e))?;
+    println!("{}", out.display());
+    Ok(())
+}
+
+fn stats(args: &[String]) -> Result<(), String> {
+    let mut source = InputSource::Default(14);
+    let mut options = Vec::new();
+    let mut ticks = None;
+    let mut out = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" | "-i" => source = InputSource::from_arg(iter.next().ok_or("--input needs a value")?),
+            "--opt" | "-o" => options.push(iter.next().ok_or("--opt needs a value")?.clone()),
+            "--ticks" | "-t" => ticks = Some(day14::parse_ticks(iter.next().ok_or("--ticks needs a value")?)?),
+            "--out" => out = Some(PathBuf::from(iter.next().ok_or("--out needs a value")?)),
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    let day = load_day14(&source, &options)?;
+    let ticks = ticks.unwrap_or_else(|| day.room.ticks().collect());
+    let csv = day14::stats_csv(&day.robots, ticks.into_iter(), day.room);
+    match out {
+        Some(out) => fs::write(&out, csv).map_err(|e| format!("could not write {}: {}", out.display(), e)),
+        None => {
+            print!("{}", csv);
+            Ok(())
+        }
+    }
+}
+
+fn replay(args: &[String]) -> Result<(), String> {
+    let mut source = InputSource::Default(15);
+    let mut mode = Mode::Narrow;
+    let mut at = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" | "-i" => source = InputSource::from_arg(iter.next().ok_or("--input needs a value")?),
+            "--wide" => mode = Mode::Wide,
+            "--at" => at.push(parse_number::<usize>(iter.next(), "--at")?),
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    if at.is_empty() {
+        return Err("replay needs at least one --at <N>".to_string());
+    }
+
+    let input = file_input::read_input(&source).map_err(|e| e.to_string())?;
+    let mut stepper = Stepper::new(day15::parse_input(&input, mode)?);
+    for n in at {
+        let frame = stepper.frame_at(n);
+        println!("after {} move(s):\n{}", stepper.position(), frame);
+    }
+    Ok(())
+}
+
+fn play(args: &[String]) -> Result<(), String> {
+    let mut source = InputSource::Default(15);
+    let mut mode = Mode::Narrow;
+    let mut record = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" | "-i" => source = InputSource::from_arg(iter.next().ok_or("--input needs a value")?),
+            "--wide" => mode = Mode::Wide,
+            "--record" => record = Some(PathBuf::from(iter.next().ok_or("--record needs a value")?)),
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    if source == InputSource::Stdin {
+        return Err("play reads keys from stdin; pass the input as a file".to_string());
+    }
+
+    let input = file_input::read_input(&source).map_err(|e| e.to_string())?;
+    let state = day15::parse_input(&input, mode)?;
+    let moves = play::run(state.clone()).map_err(|e| format!("play: {}", e))?;
+    if let Some(path) = record {
+        fs::write(&path, play::recording(&state, &moves)).map_err(|e| format!("could not write {}: {}", path.display(), e))?;
+        println!("recorded {} move(s) to {}", moves.len(), path.display());
+    }
+    Ok(())
+}
+
+fn verify(args: &[String]) -> Result<(), String> {
+    let path = match args {
+        [] => "answers.txt",
+        [flag, path] if flag == "--answers" => path.as_str(),
+        _ => return Err(USAGE.to_string()),
+    };
+    let lines = file_input::read_input(&InputSource::from_arg(path)).map_err(|e| e.to_string())?;
+    let expected = verify::parse_answers(&lines)?;
+    verify::verify(&expected)
+}
 
 fn main() {
-    //day17::test();
-    day17::solvept2();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(|s| s.as_str()) {
+        Some("list") => {
+            list();
+            Ok(())
+        }
+        Some("run") => parse_run_args(&args[1..]).and_then(|run_args| run(&run_args)),
+        Some("verify") => verify(&args[1..]),
+        Some("fetch") => fetch(&args[1..]),
+        Some("view") => view(&args[1..]),
+        Some("export") => export(&args[1..]),
+        Some("stats") => stats(&args[1..]),
+        Some("replay") => replay(&args[1..]),
+        Some("play") => play(&args[1..]),
+        Some("bench") => parse_bench_args(&args[1..]).and_then(|bench_args| bench(&bench_args)),
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,74 @@
+pub fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+pub fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 { 0 } else { (a / gcd(a, b) * b).abs() }
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Combines `t ≡ a (mod m)` and `t ≡ b (mod n)` into `t ≡ r (mod lcm(m, n))`,
+/// returning `(r, lcm)` with `0 <= r < lcm`, or `None` if they disagree.
+/// The moduli need not be coprime.
+pub fn crt(a: i64, m: i64, b: i64, n: i64) -> Option<(i64, i64)> {
+    let (g, p, _) = extended_gcd(m, n);
+    if (b - a) % g != 0 {
+        return None;
+    }
+    let l = m / g * n;
+    let k = ((b - a) / g) as i128 * p as i128 % (n / g) as i128;
+    let r = (a as i128 + k * m as i128).rem_euclid(l as i128);
+    Some((r as i64, l))
+}
+
+/// Solves `a * t ≡ b (mod m)`, returning `(t0, step)` such that the solutions
+/// are exactly `t0 + k * step`, or `None` if there are none.
+pub fn solve_linear_congruence(a: i64, b: i64, m: i64) -> Option<(i64, i64)> {
+    let a = a.rem_euclid(m);
+    let b = b.rem_euclid(m);
+    let (g, x, _) = extended_gcd(a, m);
+    if b % g != 0 {
+        return None;
+    }
+    let step = m / g;
+    let t0 = ((x as i128 * (b / g) as i128).rem_euclid(step as i128)) as i64;
+    Some((t0, step))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(101, 103), 1);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(101, 103), 10403);
+    }
+
+    #[test]
+    fn combines_congruences() {
+        assert_eq!(crt(2, 3, 3, 5), Some((8, 15)));
+        assert_eq!(crt(1, 4, 3, 6), Some((9, 12)));
+        assert_eq!(crt(1, 4, 2, 6), None);
+    }
+
+    #[test]
+    fn solves_linear_congruences() {
+        assert_eq!(solve_linear_congruence(3, 1, 7), Some((5, 7)));
+        assert_eq!(solve_linear_congruence(4, 2, 6), Some((2, 3)));
+        assert_eq!(solve_linear_congruence(4, 1, 6), None);
+        assert_eq!(solve_linear_congruence(0, 0, 5), Some((0, 1)));
+        assert_eq!(solve_linear_congruence(-2, 3, 5), Some((1, 5)));
+    }
+}
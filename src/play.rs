@@ -0,0 +1,160 @@
+use std::io::{self, Write};
+
+use crate::day15::{self, State, Stepper};
+use crate::geometry::Direction;
+use crate::terminal::{self, Control, HiddenCursor, Key, RawMode};
+
+const HELP: &str = "arrows or <>^v move  u undo  r redo  q quit";
+
+/// A game of the day 15 warehouse driven from the keyboard. Every move goes
+/// through the same pushes as the puzzle, and can be undone.
+pub struct Game {
+    pub stepper: Stepper,
+    /// Whether the last move ran into something that would not budge.
+    pub blocked: bool,
+}
+
+impl Game {
+    /// Starts from the warehouse in `state`, ignoring its scripted moves.
+    pub fn new(mut state: State) -> Game {
+        state.moves.clear();
+        Game { stepper: Stepper::new(state), blocked: false }
+    }
+
+    pub fn handle(&mut self, key: Key) -> Control {
+        let direction = match key {
+            Key::Up => Some(Direction::Up),
+            Key::Down => Some(Direction::Down),
+            Key::Left => Some(Direction::Left),
+            Key::Right => Some(Direction::Right),
+            Key::Char(c) => Direction::from_arrow(c),
+            _ => None,
+        };
+        if let Some(direction) = direction {
+            self.blocked = self.stepper.play(direction).is_none();
+            return Control::Continue;
+        }
+
+        self.blocked = false;
+        match key {
+            Key::Char('q') | Key::Esc => return Control::Quit,
+            Key::Char('u') => {
+                self.stepper.undo();
+            }
+            Key::Char('r') => {
+                self.stepper.step();
+            }
+            _ => {}
+        }
+        Control::Continue
+    }
+
+    /// The moves played so far, not counting any that were undone.
+    pub fn moves(&self) -> &[Direction] {
+        self.stepper.applied_moves()
+    }
+
+    pub fn status(&self) -> String {
+        let gps = day15::calc_answer(&self.stepper.state().cells);
+        let blocked = if self.blocked { "  blocked" } else { "" };
+        format!("moves {}  GPS {}{}", self.stepper.position(), gps, blocked)
+    }
+
+    pub fn frame(&self) -> String {
+        format!("{}{}\n{}\n", self.stepper.frame(), self.status(), HELP)
+    }
+}
+
+/// A puzzle input that replays `moves` from `start`. The map is written as
+/// played, so a game on the widened warehouse records the wide map.
+pub fn recording(start: &State, moves: &[Direction]) -> String {
+    format!("{}\n{}", day15::print_room(&start.cells, &start.robot), day15::format_moves(moves))
+}
+
+/// Plays the warehouse in the terminal until the user quits, and returns the
+/// moves they made.
+pub fn run(state: State) -> io::Result<Vec<Direction>> {
+    let _raw = RawMode::enable()?;
+    let _cursor = HiddenCursor::hide()?;
+    let keys = terminal::spawn_key_reader();
+    let mut game = Game::new(state);
+    let mut out = io::stdout();
+    write!(out, "{}", terminal::CLEAR)?;
+
+    loop {
+        write!(out, "{}{}{}", terminal::HOME, terminal::raw_lines(&game.frame()), terminal::CLEAR_BELOW)?;
+        out.flush()?;
+        match keys.recv() {
+            Ok(key) if game.handle(key) == Control::Continue => {}
+            _ => break,
+        }
+    }
+
+    Ok(game.moves().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day15::{parse_input, Day15, Mode, SMALL_EXAMPLE};
+    use crate::geometry::Point;
+    use crate::file_input::read_lines;
+    use crate::solver::{Answer, Solver};
+
+    fn game() -> Game {
+        Game::new(parse_input(&read_lines(SMALL_EXAMPLE.as_bytes()).unwrap(), Mode::Narrow).unwrap())
+    }
+
+    #[test]
+    fn plays_the_example_moves_by_hand() {
+        let mut game = game();
+        assert_eq!(game.moves(), &[]);
+        for c in "<^^>>>vv<v>>v<<".chars() {
+            game.handle(Key::Char(c));
+        }
+        assert_eq!(game.status(), "moves 15  GPS 2028  blocked");
+        assert_eq!(day15::format_moves(game.moves()), "<^^>>>vv<v>>v<<\n");
+    }
+
+    #[test]
+    fn arrow_keys_move_and_bumps_are_reported() {
+        let mut game = game();
+        game.handle(Key::Left);
+        assert!(game.blocked);
+        assert!(game.status().ends_with("blocked"));
+        game.handle(Key::Up);
+        assert!(!game.blocked);
+        assert_eq!(game.stepper.state().robot, Point::new(2, 1));
+    }
+
+    #[test]
+    fn undo_and_redo_keys() {
+        let mut game = game();
+        game.handle(Key::Char('^'));
+        game.handle(Key::Char('>'));
+        let after = game.frame();
+        game.handle(Key::Char('u'));
+        assert_eq!(game.moves(), &[Direction::Up]);
+        game.handle(Key::Char('r'));
+        assert_eq!(game.frame(), after);
+        assert_eq!(game.handle(Key::Char('q')), Control::Quit);
+    }
+
+    #[test]
+    fn records_the_map_as_played() {
+        let input = read_lines(SMALL_EXAMPLE.as_bytes()).unwrap();
+        let narrow = recording(&parse_input(&input, Mode::Narrow).unwrap(), &[Direction::Up, Direction::Left]);
+        assert_eq!(narrow, SMALL_EXAMPLE.replace("<^^>>>vv<v>>v<<", "^<"));
+
+        let start = parse_input(&input, Mode::Wide).unwrap();
+        let mut game = Game::new(start.clone());
+        for c in "<^^>>>vv<v>>v<<".chars() {
+            game.handle(Key::Char(c));
+        }
+        let text = recording(&start, game.moves());
+        assert!(text.starts_with("################\n##....[]..[]..##\n"));
+        let replay = Day15::parse(&read_lines(text.as_bytes()).unwrap()).unwrap();
+        assert_eq!(replay.part1(), Answer::Unsolved);
+        assert_eq!(replay.part2(), Answer::from(day15::calc_answer(&game.stepper.state().cells)));
+    }
+}
@@ -0,0 +1,45 @@
+use crate::solver::{parse_boxed, ParseFn};
+use crate::{day14, day15, day16, day17};
+
+pub struct Solution {
+    pub day: u8,
+    /// The parts that have a solution; the others always answer unsolved.
+    pub parts: &'static [u8],
+    pub parse: ParseFn,
+}
+
+pub const SOLUTIONS: &[Solution] = &[
+    Solution { day: 14, parts: &[1, 2], parse: parse_boxed::<day14::Day14> },
+    Solution { day: 15, parts: &[1, 2], parse: parse_boxed::<day15::Day15> },
+    Solution { day: 16, parts: &[1], parse: parse_boxed::<day16::Day16> },
+    Solution { day: 17, parts: &[1, 2], parse: parse_boxed::<day17::Day17> },
+];
+
+pub fn find(day: u8) -> Option<&'static Solution> {
+    SOLUTIONS.iter().find(|s| s.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_input::read_lines;
+    use crate::solver::Answer;
+
+    #[test]
+    fn listed_parts_match_what_the_solvers_answer() {
+        let examples = [
+            (14, include_str!("../inputs/day14-example.txt")),
+            (15, include_str!("../inputs/day15-example.txt")),
+            (16, include_str!("../inputs/day16-example1.txt")),
+            (17, include_str!("../inputs/day17-example2.txt")),
+        ];
+        for (day, text) in examples {
+            let solution = find(day).unwrap();
+            let puzzle = (solution.parse)(&read_lines(text.as_bytes()).unwrap()).unwrap();
+            for part in [1, 2] {
+                let solved = puzzle.solve(part) != Answer::Unsolved;
+                assert_eq!(solved, solution.parts.contains(&part), "day {} part {}", day, part);
+            }
+        }
+    }
+}
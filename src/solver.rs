@@ -0,0 +1,85 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i64),
+    Text(String),
+    List(Vec<i64>),
+    Unsolved,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::List(values) => {
+                let joined = values.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(",");
+                write!(f, "{}", joined)
+            }
+            Answer::Unsolved => write!(f, "unsolved"),
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Self {
+        Answer::Int(n)
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(n: i32) -> Self {
+        Answer::Int(n as i64)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::Int(n as i64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<Vec<i64>> for Answer {
+    fn from(values: Vec<i64>) -> Self {
+        Answer::List(values)
+    }
+}
+
+/// A day's puzzle: `parse` turns the raw input lines into the parsed puzzle,
+/// and each part computes its answer from it without consuming it.
+pub trait Solver {
+    fn parse(input: &[String]) -> Result<Self, String>
+    where
+        Self: Sized;
+
+    fn part1(&self) -> Answer;
+
+    fn part2(&self) -> Answer;
+
+    /// Applies a `name=value` setting given on the command line.
+    fn set_option(&mut self, name: &str, _value: &str) -> Result<(), String> {
+        Err(format!("unknown option {}", name))
+    }
+
+    fn solve(&self, part: u8) -> Answer {
+        match part {
+            1 => self.part1(),
+            2 => self.part2(),
+            _ => Answer::Unsolved,
+        }
+    }
+}
+
+pub type ParseFn = fn(&[String]) -> Result<Box<dyn Solver>, String>;
+
+/// Parses `input` as `S` behind a trait object, so days can share one table.
+pub fn parse_boxed<S: Solver + 'static>(input: &[String]) -> Result<Box<dyn Solver>, String> {
+    Ok(Box::new(S::parse(input)?))
+}
@@ -0,0 +1,160 @@
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+pub const CLEAR: &str = "\x1b[2J\x1b[H";
+pub const HOME: &str = "\x1b[H";
+pub const CLEAR_BELOW: &str = "\x1b[J";
+pub const HIDE_CURSOR: &str = "\x1b[?25l";
+pub const SHOW_CURSOR: &str = "\x1b[?25h";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Backspace,
+    Esc,
+}
+
+/// What an interactive mode should do after handling a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Quit,
+}
+
+/// Puts the terminal into raw mode (no line buffering, no echo) until dropped.
+pub struct RawMode {
+    saved: String,
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("stdin is not a terminal"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+impl RawMode {
+    pub fn enable() -> io::Result<RawMode> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        Ok(RawMode { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// Hides the cursor until dropped, so it comes back even when drawing fails.
+pub struct HiddenCursor;
+
+impl HiddenCursor {
+    pub fn hide() -> io::Result<HiddenCursor> {
+        let mut out = io::stdout();
+        write!(out, "{}", HIDE_CURSOR)?;
+        out.flush()?;
+        Ok(HiddenCursor)
+    }
+}
+
+impl Drop for HiddenCursor {
+    fn drop(&mut self) {
+        let mut out = io::stdout();
+        let _ = write!(out, "{}", SHOW_CURSOR);
+        let _ = out.flush();
+    }
+}
+
+/// Decodes one read from a raw terminal. Escape sequences for the arrow keys
+/// arrive in a single read, so a lone escape byte is the Esc key itself.
+/// Other CSI sequences (Home, Delete, function keys and so on) are skipped
+/// whole, up to their final byte.
+pub fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let key = match bytes[i] {
+            0x1b if bytes.get(i + 1) == Some(&b'[') && i + 2 < bytes.len() => {
+                let sequence = &bytes[i + 2..];
+                let len = sequence.iter().position(|b| (0x40..=0x7e).contains(b)).map_or(sequence.len(), |n| n + 1);
+                i += 1 + len;
+                match &sequence[..len] {
+                    b"A" => Some(Key::Up),
+                    b"B" => Some(Key::Down),
+                    b"C" => Some(Key::Right),
+                    b"D" => Some(Key::Left),
+                    _ => None,
+                }
+            }
+            0x1b => Some(Key::Esc),
+            b'\r' | b'\n' => Some(Key::Enter),
+            0x7f | 0x08 => Some(Key::Backspace),
+            // Ctrl-C does not raise a signal in raw mode.
+            0x03 => Some(Key::Char('q')),
+            b => Some(Key::Char(b as char)),
+        };
+        keys.extend(key);
+        i += 1;
+    }
+    keys
+}
+
+/// Reads keys from stdin on a background thread, so callers can wait for a
+/// key with a timeout while animating.
+pub fn spawn_key_reader() -> Receiver<Key> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buffer = [0; 16];
+        while let Ok(n) = stdin.read(&mut buffer) {
+            if n == 0 {
+                break;
+            }
+            for key in parse_keys(&buffer[..n]) {
+                if sender.send(key).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+/// Text written while in raw mode needs explicit carriage returns.
+pub fn raw_lines(text: &str) -> String {
+    text.replace('\n', "\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_arrows_and_characters() {
+        assert_eq!(parse_keys(b"\x1b[A\x1b[Dq"), vec![Key::Up, Key::Left, Key::Char('q')]);
+        assert_eq!(parse_keys(b"\x1b"), vec![Key::Esc]);
+        assert_eq!(parse_keys(b"12\r\x7f"), vec![Key::Char('1'), Key::Char('2'), Key::Enter, Key::Backspace]);
+    }
+
+    #[test]
+    fn skips_other_escape_sequences_whole() {
+        assert_eq!(parse_keys(b"\x1b[3~"), vec![]);
+        assert_eq!(parse_keys(b"\x1b[1;5Cq\x1b[15~\x1b[B"), vec![Key::Char('q'), Key::Down]);
+        assert_eq!(parse_keys(b"\x1b[H\x1b[F"), vec![]);
+    }
+
+    #[test]
+    fn adds_carriage_returns() {
+        assert_eq!(raw_lines("ab\ncd\n"), "ab\r\ncd\r\n");
+    }
+}
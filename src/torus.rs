@@ -0,0 +1,136 @@
+use crate::math;
+
+/// A point moving at a constant integer velocity, one step per tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Particle<const D: usize> {
+    pub position: [i64; D],
+    pub velocity: [i64; D],
+}
+
+/// A `D`-dimensional grid whose opposite edges are joined, so particles that
+/// leave one side come back in on the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Torus<const D: usize> {
+    pub size: [i64; D],
+}
+
+/// Two particles that share a cell at tick `first` and again every `every` ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collision {
+    pub particles: (usize, usize),
+    pub first: i64,
+    pub every: i64,
+}
+
+impl<const D: usize> Torus<D> {
+    pub fn wrap(&self, cell: [i64; D]) -> [i64; D] {
+        let mut wrapped = cell;
+        for (c, size) in wrapped.iter_mut().zip(self.size) {
+            *c = c.rem_euclid(size);
+        }
+        wrapped
+    }
+
+    /// Every particle is back where it started after this many ticks.
+    pub fn period(&self) -> i64 {
+        self.size.iter().fold(1, |period, size| math::lcm(period, *size))
+    }
+
+    /// Where `particle` is after `tick` ticks; negative ticks run backwards.
+    pub fn position(&self, particle: &Particle<D>, tick: i64) -> [i64; D] {
+        std::array::from_fn(|d| {
+            let size = self.size[d] as i128;
+            let moved = particle.position[d] as i128 + particle.velocity[d] as i128 * (tick as i128).rem_euclid(size);
+            moved.rem_euclid(size) as i64
+        })
+    }
+
+    /// Solves `a * t ≡ b` on every axis at once, where `axis(d)` gives `(a, b)`
+    /// for axis `d`. Each axis has its own solutions; the ticks that satisfy
+    /// them all are their combination.
+    fn solve(&self, axis: impl Fn(usize) -> (i64, i64)) -> Option<(i64, i64)> {
+        (0..D).try_fold((0, 1), |(t, step), d| {
+            let (a, b) = axis(d);
+            let (td, sd) = math::solve_linear_congruence(a, b, self.size[d])?;
+            math::crt(t, step, td, sd)
+        })
+    }
+
+    /// When `a` and `b` share a cell, as `(first, every)`, or `None` if never.
+    pub fn collision(&self, a: &Particle<D>, b: &Particle<D>) -> Option<(i64, i64)> {
+        self.solve(|d| (a.velocity[d] - b.velocity[d], b.position[d] - a.position[d]))
+    }
+
+    /// When `particle` is at `cell`, as `(first, every)`, or `None` if never.
+    pub fn arrival(&self, particle: &Particle<D>, cell: [i64; D]) -> Option<(i64, i64)> {
+        let cell = self.wrap(cell);
+        self.solve(|d| (particle.velocity[d], cell[d] - particle.position[d]))
+    }
+
+    /// Every pair of particles that ever coincides.
+    pub fn collisions(&self, particles: &[Particle<D>]) -> Vec<Collision> {
+        let mut found = Vec::new();
+        for i in 0..particles.len() {
+            for j in i + 1..particles.len() {
+                if let Some((first, every)) = self.collision(&particles[i], &particles[j]) {
+                    found.push(Collision { particles: (i, j), first, every });
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticks_where(torus: &Torus<3>, hit: impl Fn(i64) -> bool) -> Vec<i64> {
+        (0..torus.period()).filter(|t| hit(*t)).collect()
+    }
+
+    fn expand(found: Option<(i64, i64)>, period: i64) -> Vec<i64> {
+        found.map_or(Vec::new(), |(first, every)| (first..period).step_by(every as usize).collect())
+    }
+
+    #[test]
+    fn moves_and_wraps() {
+        let torus = Torus { size: [5, 4] };
+        let particle = Particle { position: [1, 1], velocity: [3, -2] };
+        assert_eq!(torus.position(&particle, 2), [2, 1]);
+        assert_eq!(torus.position(&particle, -1), [3, 3]);
+        assert_eq!(torus.position(&particle, torus.period()), particle.position);
+        assert_eq!(torus.period(), 20);
+        assert_eq!(torus.wrap([-1, 9]), [4, 1]);
+    }
+
+    #[test]
+    fn collisions_and_arrivals_match_simulation_in_3d() {
+        let torus = Torus { size: [4, 6, 3] };
+        let particles = [
+            Particle { position: [0, 0, 0], velocity: [1, 2, 1] },
+            Particle { position: [2, 4, 1], velocity: [-1, 0, 2] },
+            Particle { position: [3, 1, 2], velocity: [2, -3, 0] },
+            Particle { position: [0, 2, 0], velocity: [1, 2, 1] },
+            Particle { position: [1, 5, 2], velocity: [0, 0, 0] },
+        ];
+        let period = torus.period();
+        let found = torus.collisions(&particles);
+        for i in 0..particles.len() {
+            for j in i + 1..particles.len() {
+                let simulated =
+                    ticks_where(&torus, |t| torus.position(&particles[i], t) == torus.position(&particles[j], t));
+                let predicted = found.iter().find(|c| c.particles == (i, j)).map(|c| (c.first, c.every));
+                assert_eq!(expand(predicted, period), simulated, "particles {} and {}", i, j);
+            }
+        }
+        assert!(!found.is_empty());
+
+        for particle in &particles {
+            for cell in [[1, 2, 0], [3, 5, 2], [0, 0, 0], [-1, 7, 4]] {
+                let simulated = ticks_where(&torus, |t| torus.position(particle, t) == torus.wrap(cell));
+                assert_eq!(expand(torus.arrival(particle, cell), period), simulated);
+            }
+        }
+    }
+}
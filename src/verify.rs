@@ -0,0 +1,101 @@
+use std::io;
+use std::path::PathBuf;
+
+use crate::file_input::{self, InputSource};
+use crate::solutions;
+
+/// One line of the answers file: `<day> <part> <input> <answer>`.
+pub struct Expected {
+    pub day: u8,
+    pub part: u8,
+    pub input: PathBuf,
+    pub answer: String,
+}
+
+pub enum Outcome {
+    Pass,
+    Mismatch(String),
+    Fail(String),
+    Skipped(String),
+}
+
+pub fn parse_answers(lines: &[String]) -> Result<Vec<Expected>, String> {
+    let mut expected = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = line.split_whitespace().collect::<Vec<&str>>();
+        if fields.len() != 4 {
+            return Err(format!("line {}: expected `<day> <part> <input> <answer>`", i + 1));
+        }
+        let day = fields[0].parse().map_err(|_| format!("line {}: invalid day {}", i + 1, fields[0]))?;
+        let part = fields[1].parse().map_err(|_| format!("line {}: invalid part {}", i + 1, fields[1]))?;
+        expected.push(Expected {
+            day,
+            part,
+            input: PathBuf::from(fields[2]),
+            answer: fields[3].to_string(),
+        });
+    }
+    Ok(expected)
+}
+
+pub fn check(expected: &Expected) -> Outcome {
+    let solution = match solutions::find(expected.day) {
+        Some(solution) => solution,
+        None => return Outcome::Fail(format!("no solution for day {}", expected.day)),
+    };
+    let input = match file_input::read_input(&InputSource::Path(expected.input.clone())) {
+        Ok(input) => input,
+        Err(e) if e.error.kind() == io::ErrorKind::NotFound => return Outcome::Skipped("no input".to_string()),
+        Err(e) => return Outcome::Fail(e.to_string()),
+    };
+    let puzzle = match (solution.parse)(&input) {
+        Ok(puzzle) => puzzle,
+        Err(e) => return Outcome::Fail(e),
+    };
+    let answer = puzzle.solve(expected.part).to_string();
+    if answer == expected.answer {
+        Outcome::Pass
+    } else {
+        Outcome::Mismatch(answer)
+    }
+}
+
+/// Checks every expected answer and prints one line per entry. Returns an
+/// error if anything did not pass; missing inputs are only reported.
+pub fn verify(expected: &[Expected]) -> Result<(), String> {
+    let mut passed = 0;
+    let mut mismatched = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+    for entry in expected {
+        let label = format!("day {:>2} part {} {}", entry.day, entry.part, entry.input.display());
+        match check(entry) {
+            Outcome::Pass => {
+                passed += 1;
+                println!("pass      {}: {}", label, entry.answer);
+            }
+            Outcome::Mismatch(actual) => {
+                mismatched += 1;
+                println!("mismatch  {}: expected {}, got {}", label, entry.answer, actual);
+            }
+            Outcome::Fail(e) => {
+                failed += 1;
+                println!("fail      {}: {}", label, e);
+            }
+            Outcome::Skipped(reason) => {
+                skipped += 1;
+                println!("skipped   {}: {}", label, reason);
+            }
+        }
+    }
+    println!("{} passed, {} mismatched, {} failed, {} skipped", passed, mismatched, failed, skipped);
+
+    if mismatched + failed > 0 {
+        return Err(format!("{} answer(s) did not verify", mismatched + failed));
+    }
+    Ok(())
+}
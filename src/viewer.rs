@@ -0,0 +1,185 @@
+use std::io::{self, Write};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+use crate::day14::{self, Robot, Room};
+use crate::terminal::{self, Control, HiddenCursor, Key, RawMode};
+
+const MAX_FPS: u32 = 64;
+const BIG_STEP: i64 = 100;
+const HELP: &str = "←/→ step  ↑/↓ step 100  space play/pause  +/- speed  g jump  q quit";
+
+/// Where the day 14 viewer is in the robots' cycle and how it is moving
+/// through it. Ticks wrap around the room's period in both directions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Playback {
+    pub tick: i64,
+    pub period: i64,
+    pub playing: bool,
+    pub fps: u32,
+    /// Digits typed after `g`, while a jump is being entered.
+    pub jump: Option<String>,
+}
+
+impl Playback {
+    pub fn new(period: i64, tick: i64) -> Playback {
+        Playback { tick: tick.rem_euclid(period), period, playing: false, fps: 8, jump: None }
+    }
+
+    pub fn step(&mut self, by: i64) {
+        self.tick = (self.tick + by).rem_euclid(self.period);
+    }
+
+    pub fn frame_delay(&self) -> Duration {
+        Duration::from_millis(1000 / self.fps as u64)
+    }
+
+    pub fn handle(&mut self, key: Key) -> Control {
+        if let Some(jump) = &mut self.jump {
+            match key {
+                Key::Char(c) if c.is_ascii_digit() => jump.push(c),
+                Key::Backspace => {
+                    jump.pop();
+                }
+                Key::Enter => {
+                    if let Ok(tick) = jump.parse::<i64>() {
+                        self.tick = tick.rem_euclid(self.period);
+                    }
+                    self.jump = None;
+                }
+                Key::Esc => self.jump = None,
+                _ => {}
+            }
+            return Control::Continue;
+        }
+
+        match key {
+            Key::Char('q') | Key::Esc => return Control::Quit,
+            Key::Right | Key::Char('n') | Key::Char('l') => self.step(1),
+            Key::Left | Key::Char('p') | Key::Char('h') => self.step(-1),
+            Key::Up => self.step(BIG_STEP),
+            Key::Down => self.step(-BIG_STEP),
+            Key::Char(' ') => self.playing = !self.playing,
+            Key::Char('+') | Key::Char('=') => self.fps = (self.fps * 2).min(MAX_FPS),
+            Key::Char('-') => self.fps = (self.fps / 2).max(1),
+            Key::Char('g') => {
+                self.playing = false;
+                self.jump = Some(String::new());
+            }
+            _ => {}
+        }
+        Control::Continue
+    }
+
+    pub fn status(&self) -> String {
+        let state = if self.playing { format!("playing at {} fps", self.fps) } else { "paused".to_string() };
+        match &self.jump {
+            Some(jump) => format!("tick {}/{}  jump to: {}_", self.tick, self.period, jump),
+            None => format!("tick {}/{}  {}", self.tick, self.period, state),
+        }
+    }
+}
+
+/// The room at the playback's tick with a status line underneath. Each frame
+/// is computed straight from the robots' starting positions, so any tick costs
+/// the same to draw.
+pub fn frame(robots: &[Robot], room: Room, playback: &Playback) -> String {
+    let counts = day14::room_after_times(robots, playback.tick as i32, room);
+    format!("{}{}\n{}\n", day14::print_room(&counts), playback.status(), HELP)
+}
+
+/// Plays the robots back in the terminal until the user quits.
+pub fn run(robots: &[Robot], room: Room, start: i64) -> io::Result<()> {
+    let _raw = RawMode::enable()?;
+    let _cursor = HiddenCursor::hide()?;
+    let keys = terminal::spawn_key_reader();
+    let mut playback = Playback::new(room.period(), start);
+    let mut out = io::stdout();
+    write!(out, "{}", terminal::CLEAR)?;
+
+    loop {
+        let text = frame(robots, room, &playback);
+        write!(out, "{}{}{}", terminal::HOME, terminal::raw_lines(&text), terminal::CLEAR_BELOW)?;
+        out.flush()?;
+
+        let key = if playback.playing {
+            match keys.recv_timeout(playback.frame_delay()) {
+                Ok(key) => Some(key),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        } else {
+            match keys.recv() {
+                Ok(key) => Some(key),
+                Err(_) => break,
+            }
+        };
+        match key {
+            Some(key) => {
+                if playback.handle(key) == Control::Quit {
+                    break;
+                }
+            }
+            None => playback.step(1),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Point, Vec2};
+
+    #[test]
+    fn steps_wrap_around_the_period() {
+        let mut playback = Playback::new(77, 0);
+        playback.handle(Key::Left);
+        assert_eq!(playback.tick, 76);
+        playback.handle(Key::Right);
+        playback.handle(Key::Char('n'));
+        assert_eq!(playback.tick, 1);
+        playback.handle(Key::Up);
+        assert_eq!(playback.tick, 24);
+    }
+
+    #[test]
+    fn jumps_to_a_typed_tick() {
+        let mut playback = Playback::new(10_403, 0);
+        playback.playing = true;
+        for key in [Key::Char('g'), Key::Char('7'), Key::Char('1'), Key::Char('x'), Key::Char('5')] {
+            playback.handle(key);
+        }
+        assert!(!playback.playing);
+        assert_eq!(playback.status(), "tick 0/10403  jump to: 715_");
+        playback.handle(Key::Backspace);
+        playback.handle(Key::Char('2'));
+        playback.handle(Key::Enter);
+        assert_eq!(playback.tick, 712);
+        assert_eq!(playback.jump, None);
+        assert_eq!(playback.handle(Key::Char('q')), Control::Quit);
+    }
+
+    #[test]
+    fn speed_stays_in_range() {
+        let mut playback = Playback::new(77, 0);
+        for _ in 0..10 {
+            playback.handle(Key::Char('+'));
+        }
+        assert_eq!(playback.fps, MAX_FPS);
+        for _ in 0..10 {
+            playback.handle(Key::Char('-'));
+        }
+        assert_eq!(playback.fps, 1);
+        assert_eq!(playback.frame_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn frames_show_the_room_at_the_tick() {
+        let robots = vec![Robot { init_pos: Point::new(0, 0), direction: Vec2::new(1, 1) }];
+        let room = Room { width: 3, height: 2 };
+        let text = frame(&robots, room, &Playback::new(room.period(), 4));
+        assert_eq!(text.lines().take(3).collect::<Vec<_>>(), vec![".1.", "...", "tick 4/6  paused"]);
+    }
+}
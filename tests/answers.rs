@@ -0,0 +1,15 @@
+use aoc2024::file_input::{self, InputSource};
+use aoc2024::verify::{self, Outcome};
+
+#[test]
+fn stored_answers_still_verify() {
+    let lines = file_input::read_input(&InputSource::from_arg("answers.txt")).unwrap();
+    for expected in verify::parse_answers(&lines).unwrap() {
+        let label = format!("day {} part {} {}", expected.day, expected.part, expected.input.display());
+        match verify::check(&expected) {
+            Outcome::Pass | Outcome::Skipped(_) => {}
+            Outcome::Mismatch(actual) => panic!("{}: expected {}, got {}", label, expected.answer, actual),
+            Outcome::Fail(e) => panic!("{}: {}", label, e),
+        }
+    }
+}